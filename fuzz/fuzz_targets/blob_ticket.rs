@@ -0,0 +1,8 @@
+#![no_main]
+
+use iroh_base::ticket::{BlobTicket, Ticket};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = BlobTicket::from_bytes(data);
+});
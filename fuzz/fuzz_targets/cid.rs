@@ -0,0 +1,8 @@
+#![no_main]
+
+use iroh_base::hash::HashAndFormat;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = HashAndFormat::from_cid_bytes(data);
+});
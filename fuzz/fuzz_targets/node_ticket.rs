@@ -0,0 +1,8 @@
+#![no_main]
+
+use iroh_base::ticket::{NodeTicket, Ticket};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = NodeTicket::from_bytes(data);
+});
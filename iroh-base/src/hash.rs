@@ -58,6 +58,132 @@ impl Hash {
     pub fn fmt_short(&self) -> String {
         base32::fmt_short(self.as_bytes())
     }
+
+    /// The [`HashAlgorithm`] used to compute this hash.
+    pub const fn algorithm(&self) -> HashAlgorithm {
+        HashAlgorithm::Blake3
+    }
+
+    /// Encode the hash as a [multihash].
+    ///
+    /// The encoding is self describing: it is prefixed with the algorithm code and the
+    /// digest length, so it stays valid if further algorithms are supported.
+    ///
+    /// [multihash]: https://github.com/multiformats/multihash
+    pub fn to_multihash(&self) -> Vec<u8> {
+        let algorithm = self.algorithm();
+        let mut out = Vec::with_capacity(2 + algorithm.digest_len());
+        write_varint(&mut out, algorithm.multihash_code());
+        write_varint(&mut out, algorithm.digest_len() as u64);
+        out.extend_from_slice(self.as_bytes());
+        out
+    }
+
+    /// Decode a hash from its [multihash] encoding.
+    ///
+    /// [multihash]: https://github.com/multiformats/multihash
+    pub fn from_multihash(bytes: &[u8]) -> Result<Self, MultihashError> {
+        let (code, bytes) = read_varint(bytes)?;
+        let algorithm = HashAlgorithm::from_multihash_code(code)
+            .ok_or(MultihashError::UnsupportedAlgorithm(code))?;
+        let (len, bytes) = read_varint(bytes)?;
+        if len != algorithm.digest_len() as u64 {
+            return Err(MultihashError::DigestLength(len));
+        }
+        if bytes.len() < algorithm.digest_len() {
+            return Err(MultihashError::DigestLength(bytes.len() as u64));
+        }
+        let (digest, rest) = bytes.split_at(algorithm.digest_len());
+        if !rest.is_empty() {
+            return Err(MultihashError::TrailingBytes);
+        }
+        let digest: [u8; 32] = digest.try_into().expect("checked length");
+        Ok(Hash::from_bytes(digest))
+    }
+}
+
+/// The hash algorithm used to compute a [`Hash`].
+///
+/// Only BLAKE3 is implemented. Self describing encodings such as
+/// [`Hash::to_multihash`] carry the algorithm, so that adding an algorithm later does
+/// not invalidate existing data.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
+#[non_exhaustive]
+pub enum HashAlgorithm {
+    /// BLAKE3 with a 32 byte digest.
+    #[default]
+    Blake3,
+}
+
+impl HashAlgorithm {
+    /// The multihash code of this algorithm.
+    pub const fn multihash_code(&self) -> u64 {
+        match self {
+            HashAlgorithm::Blake3 => 0x1e,
+        }
+    }
+
+    /// The length of the digest in bytes.
+    pub const fn digest_len(&self) -> usize {
+        match self {
+            HashAlgorithm::Blake3 => 32,
+        }
+    }
+
+    /// Look up an algorithm by its multihash code.
+    pub const fn from_multihash_code(code: u64) -> Option<Self> {
+        match code {
+            0x1e => Some(HashAlgorithm::Blake3),
+            _ => None,
+        }
+    }
+}
+
+/// Error decoding a multihash encoded [`Hash`].
+#[derive(Debug, thiserror::Error)]
+pub enum MultihashError {
+    /// An unsigned varint was truncated, too long or not minimally encoded.
+    #[error("invalid varint")]
+    Varint,
+    /// The multihash uses an algorithm that is not supported.
+    #[error("unsupported hash algorithm {0:#x}")]
+    UnsupportedAlgorithm(u64),
+    /// The digest length does not match the algorithm.
+    #[error("invalid digest length {0}")]
+    DigestLength(u64),
+    /// There were bytes left after the digest.
+    #[error("trailing bytes after digest")]
+    TrailingBytes,
+}
+
+/// Append `value` as an unsigned LEB128 varint, as used by the multiformats.
+pub(crate) fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// Read an unsigned LEB128 varint, returning the value and the remaining bytes.
+///
+/// Only the minimal encoding is accepted, as required by the multiformats spec.
+pub(crate) fn read_varint(bytes: &[u8]) -> Result<(u64, &[u8]), MultihashError> {
+    let mut value = 0u64;
+    // the multiformats spec limits varints to 9 bytes
+    for (i, byte) in bytes.iter().take(9).enumerate() {
+        value |= u64::from(byte & 0x7f) << (i * 7);
+        if byte & 0x80 == 0 {
+            // a trailing zero byte only adds padding, e.g. `9e 00` for 0x1e
+            if i > 0 && *byte == 0 {
+                break;
+            }
+            return Ok((value, &bytes[i + 1..]));
+        }
+    }
+    Err(MultihashError::Varint)
 }
 
 impl AsRef<[u8]> for Hash {
@@ -218,6 +344,27 @@ impl BlobFormat {
     pub const fn is_hash_seq(&self) -> bool {
         matches!(self, BlobFormat::HashSeq)
     }
+
+    /// The [multicodec] code used for this format in a CID.
+    ///
+    /// [multicodec]: https://github.com/multiformats/multicodec
+    pub const fn cid_codec(&self) -> u64 {
+        match self {
+            BlobFormat::Raw => 0x55,
+            BlobFormat::HashSeq => 0x80,
+        }
+    }
+
+    /// Look up a format by its [multicodec] code.
+    ///
+    /// [multicodec]: https://github.com/multiformats/multicodec
+    pub const fn from_cid_codec(code: u64) -> Option<Self> {
+        match code {
+            0x55 => Some(BlobFormat::Raw),
+            0x80 => Some(BlobFormat::HashSeq),
+            _ => None,
+        }
+    }
 }
 
 /// A hash and format pair
@@ -327,6 +474,81 @@ impl HashAndFormat {
             format: BlobFormat::HashSeq,
         }
     }
+
+    /// Encode as a binary [CIDv1].
+    ///
+    /// The hash is encoded as a BLAKE3 multihash, the format as the `raw` or
+    /// `blake3-hashseq` codec.
+    ///
+    /// [CIDv1]: https://github.com/multiformats/cid
+    pub fn to_cid_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(4 + self.hash.algorithm().digest_len());
+        write_varint(&mut out, 1);
+        write_varint(&mut out, self.format.cid_codec());
+        out.extend_from_slice(&self.hash.to_multihash());
+        out
+    }
+
+    /// Encode as a [CIDv1] string, using the lower case base32 multibase.
+    ///
+    /// This is the default text representation of CIDv1 used by IPFS, e.g. `bafkr4i...`
+    /// for a raw blob.
+    ///
+    /// [CIDv1]: https://github.com/multiformats/cid
+    pub fn to_cid(&self) -> String {
+        let mut out = "b".to_string();
+        base32::fmt_append(self.to_cid_bytes(), &mut out);
+        out
+    }
+
+    /// Decode from a binary [CIDv1].
+    ///
+    /// [CIDv1]: https://github.com/multiformats/cid
+    pub fn from_cid_bytes(bytes: &[u8]) -> Result<Self, CidError> {
+        let (version, bytes) = read_varint(bytes)?;
+        if version != 1 {
+            return Err(CidError::Version(version));
+        }
+        let (codec, bytes) = read_varint(bytes)?;
+        let format = BlobFormat::from_cid_codec(codec).ok_or(CidError::Codec(codec))?;
+        let hash = Hash::from_multihash(bytes)?;
+        Ok(Self { hash, format })
+    }
+
+    /// Decode from a base32 encoded [CIDv1] string.
+    ///
+    /// Only CIDs using a BLAKE3 multihash can be represented, CIDv0 and other hash
+    /// algorithms are rejected.
+    ///
+    /// [CIDv1]: https://github.com/multiformats/cid
+    pub fn from_cid(s: &str) -> Result<Self, CidError> {
+        let rest = s
+            .strip_prefix('b')
+            .or_else(|| s.strip_prefix('B'))
+            .ok_or(CidError::Multibase)?;
+        let bytes = base32::parse_vec(rest)?;
+        Self::from_cid_bytes(&bytes)
+    }
+}
+
+/// Error decoding a [`HashAndFormat`] from a CID.
+#[derive(Debug, thiserror::Error)]
+pub enum CidError {
+    /// The CID string does not use the base32 multibase.
+    #[error("unsupported multibase, expected base32")]
+    Multibase,
+    /// Base32 decoding failed.
+    #[error("base32: {0}")]
+    Base32(#[from] base32::DecodeError),
+    /// Only CIDv1 is supported.
+    #[error("unsupported CID version {0}")]
+    Version(u64),
+    /// The codec does not map to a [`BlobFormat`].
+    #[error("unsupported codec {0:#x}")]
+    Codec(u64),
+    /// Decoding the multihash failed.
+    #[error("multihash: {0}")]
+    Multihash(#[from] MultihashError),
 }
 
 impl fmt::Display for HashAndFormat {
@@ -349,17 +571,23 @@ impl FromStr for HashAndFormat {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let s = s.as_bytes();
+        let bytes = s.as_bytes();
         let mut hash = [0u8; 32];
-        match s.len() {
+        match bytes.len() {
             64 => {
-                hex::decode_to_slice(s, &mut hash)?;
+                hex::decode_to_slice(bytes, &mut hash)?;
                 Ok(Self::raw(hash.into()))
             }
-            65 if s[0].to_ascii_lowercase() == b's' => {
-                hex::decode_to_slice(&s[1..], &mut hash)?;
+            65 if bytes[0].to_ascii_lowercase() == b's' => {
+                hex::decode_to_slice(&bytes[1..], &mut hash)?;
                 Ok(Self::hash_seq(hash.into()))
             }
+            _ if bytes
+                .first()
+                .is_some_and(|c| c.to_ascii_lowercase() == b'b') =>
+            {
+                Ok(Self::from_cid(s)?)
+            }
             _ => anyhow::bail!("invalid hash and format"),
         }
     }
@@ -430,6 +658,53 @@ mod tests {
         assert_eq!(hash, Hash::EMPTY);
     }
 
+    #[test]
+    fn test_hash_multihash() {
+        let expected = parse_hexdump(
+            r"
+            1e # blake3
+            20 # digest length (32)
+            af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262 # hash
+        ",
+        )
+        .unwrap();
+        assert_eq!(Hash::from_multihash(&expected).unwrap(), Hash::EMPTY);
+
+        // sha2-256
+        let mut sha = expected.clone();
+        sha[0] = 0x12;
+        assert!(matches!(
+            Hash::from_multihash(&sha),
+            Err(MultihashError::UnsupportedAlgorithm(0x12))
+        ));
+        assert!(matches!(
+            Hash::from_multihash(&expected[..20]),
+            Err(MultihashError::DigestLength(_))
+        ));
+        let mut trailing = expected.clone();
+        trailing.push(0);
+        assert!(matches!(
+            Hash::from_multihash(&trailing),
+            Err(MultihashError::TrailingBytes)
+        ));
+        assert_eq_hex!(Hash::EMPTY.to_multihash(), expected);
+    }
+
+    #[test]
+    fn test_varint_roundtrip() {
+        for value in [0, 1, 0x7f, 0x80, 0x3fff, 0x4000, u32::MAX as u64] {
+            let mut buf = Vec::new();
+            write_varint(&mut buf, value);
+            let (decoded, rest) = read_varint(&buf).unwrap();
+            assert_eq!(decoded, value);
+            assert!(rest.is_empty());
+        }
+        assert!(read_varint(&[0x80]).is_err());
+        // non-minimal encodings are rejected
+        assert!(read_varint(&[0x9e, 0x00]).is_err());
+        assert!(read_varint(&[0x80, 0x80, 0x00]).is_err());
+    }
+
     #[test]
     fn hash_wire_format() {
         let hash = Hash::from([0xab; 32]);
@@ -548,6 +823,43 @@ mod tests {
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn test_hash_and_format_cid() {
+        let haf = HashAndFormat::raw(Hash::EMPTY);
+        let cid = haf.to_cid();
+        assert_eq!(
+            cid,
+            "bafkr4ifpcne3t5pzugtkaqcn5i3nzskjtpfslsnnyejlpte2spfoihzsmi"
+        );
+        assert_eq!(HashAndFormat::from_cid(&cid).unwrap(), haf);
+        assert_eq!(
+            HashAndFormat::from_cid(&cid.to_ascii_uppercase()).unwrap(),
+            haf
+        );
+
+        assert_eq!(cid.parse::<HashAndFormat>().unwrap(), haf);
+
+        let haf = HashAndFormat::hash_seq(Hash::new("hello"));
+        assert_eq!(HashAndFormat::from_cid(&haf.to_cid()).unwrap(), haf);
+        assert_eq!(haf.to_cid().parse::<HashAndFormat>().unwrap(), haf);
+        let bytes = haf.to_cid_bytes();
+        assert_eq!(&bytes[..3], &[0x01, 0x80, 0x01]);
+        assert_eq!(HashAndFormat::from_cid_bytes(&bytes).unwrap(), haf);
+
+        // CIDv0, base58btc
+        assert!(matches!(
+            HashAndFormat::from_cid("QmbWqxBEKC3P8tqsKc98xmWNzrzDtRLMiMPL8wBuTGsMnR"),
+            Err(CidError::Multibase)
+        ));
+        // dag-pb codec
+        let mut bytes = HashAndFormat::raw(Hash::EMPTY).to_cid_bytes();
+        bytes[1] = 0x70;
+        assert!(matches!(
+            HashAndFormat::from_cid_bytes(&bytes),
+            Err(CidError::Codec(0x70))
+        ));
+    }
+
     #[test]
     fn test_hash_and_format_postcard() {
         let haf = HashAndFormat::raw(Hash::new("hello"));
@@ -564,3 +876,55 @@ mod tests {
         assert_eq!(haf, de);
     }
 }
+
+#[cfg(test)]
+mod proptests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    fn hash_and_format() -> impl Strategy<Value = HashAndFormat> {
+        (prop::array::uniform32(any::<u8>()), any::<bool>()).prop_map(|(bytes, raw)| {
+            let hash = Hash::from_bytes(bytes);
+            if raw {
+                HashAndFormat::raw(hash)
+            } else {
+                HashAndFormat::hash_seq(hash)
+            }
+        })
+    }
+
+    proptest! {
+        // Decoding untrusted input must fail with an error, never panic.
+        #[test]
+        fn multihash_arbitrary_bytes(bytes in prop::collection::vec(any::<u8>(), 0..64)) {
+            let _ = Hash::from_multihash(&bytes);
+        }
+
+        #[test]
+        fn cid_arbitrary_bytes(bytes in prop::collection::vec(any::<u8>(), 0..64)) {
+            let _ = HashAndFormat::from_cid_bytes(&bytes);
+        }
+
+        #[test]
+        fn hash_and_format_arbitrary_str(s in "\\PC{0,80}") {
+            let _ = s.parse::<HashAndFormat>();
+        }
+
+        #[test]
+        fn cid_roundtrip(hf in hash_and_format()) {
+            let decoded = HashAndFormat::from_cid_bytes(&hf.to_cid_bytes()).unwrap();
+            prop_assert_eq!(decoded, hf);
+            let decoded: HashAndFormat = hf.to_cid().parse().unwrap();
+            prop_assert_eq!(decoded, hf);
+        }
+
+        // Every strict prefix of a valid CID is rejected.
+        #[test]
+        fn cid_truncated(hf in hash_and_format(), cut in any::<prop::sample::Index>()) {
+            let bytes = hf.to_cid_bytes();
+            let cut = cut.index(bytes.len());
+            prop_assert!(HashAndFormat::from_cid_bytes(&bytes[..cut]).is_err());
+        }
+    }
+}
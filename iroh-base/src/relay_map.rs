@@ -2,7 +2,7 @@
 
 use std::{collections::BTreeMap, fmt, sync::Arc};
 
-use anyhow::{ensure, Result};
+use anyhow::{bail, ensure, Result};
 use serde::{Deserialize, Serialize};
 
 pub use crate::relay_url::RelayUrl;
@@ -31,6 +31,24 @@ impl RelayMap {
         self.nodes.keys()
     }
 
+    /// Returns a [`RelayMapBuilder`] to construct a [`RelayMap`].
+    ///
+    /// ```
+    /// # use iroh_base::relay_map::RelayMap;
+    /// let map = RelayMap::builder()
+    ///     .node("https://relay1.example.com".parse()?)
+    ///     .stun_port(3479)
+    ///     .node("https://stun.example.com".parse()?)
+    ///     .stun_only(true)
+    ///     .quic(None)
+    ///     .build()?;
+    /// assert_eq!(map.len(), 2);
+    /// # anyhow::Ok(())
+    /// ```
+    pub fn builder() -> RelayMapBuilder {
+        RelayMapBuilder::default()
+    }
+
     /// Create an empty relay map.
     pub fn empty() -> Self {
         Self {
@@ -107,6 +125,159 @@ impl RelayMap {
         }
         Ok(RelayMap { nodes: map.into() })
     }
+
+    /// Merges the nodes of `other` into this map, returning the merged [`RelayMap`].
+    ///
+    /// Nodes are identified by their [`RelayUrl`].  If both maps contain a node with the
+    /// same url but a different configuration, `policy` decides which one is used.
+    pub fn merge(&self, other: &RelayMap, policy: MergePolicy) -> Result<Self> {
+        let mut nodes = (*self.nodes).clone();
+        for (url, node) in other.nodes.iter() {
+            match nodes.get(url) {
+                Some(existing) if existing != node => match policy {
+                    MergePolicy::KeepExisting => {}
+                    MergePolicy::Replace => {
+                        nodes.insert(url.clone(), node.clone());
+                    }
+                    MergePolicy::Error => bail!("Conflicting configuration for relay {url}"),
+                },
+                Some(_) => {}
+                None => {
+                    nodes.insert(url.clone(), node.clone());
+                }
+            }
+        }
+        Ok(RelayMap {
+            nodes: Arc::new(nodes),
+        })
+    }
+
+    /// Returns the changes needed to go from this map to `other`.
+    pub fn diff(&self, other: &RelayMap) -> RelayMapDiff {
+        let mut diff = RelayMapDiff::default();
+        for (url, node) in self.nodes.iter() {
+            match other.nodes.get(url) {
+                None => diff.removed.push(node.clone()),
+                Some(new) if new != node => diff.changed.push((node.clone(), new.clone())),
+                Some(_) => {}
+            }
+        }
+        for (url, node) in other.nodes.iter() {
+            if !self.nodes.contains_key(url) {
+                diff.added.push(node.clone());
+            }
+        }
+        diff
+    }
+}
+
+/// How to resolve conflicts in [`RelayMap::merge`].
+///
+/// A conflict occurs when both maps contain a node with the same [`RelayUrl`] but a
+/// different configuration.  Identical nodes never conflict.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MergePolicy {
+    /// Keep the node of the map being merged into.
+    #[default]
+    KeepExisting,
+    /// Replace the node with the one from the other map.
+    Replace,
+    /// Fail the merge.
+    Error,
+}
+
+/// The differences between two [`RelayMap`]s, see [`RelayMap::diff`].
+///
+/// All lists are sorted by [`RelayUrl`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RelayMapDiff {
+    /// Nodes only present in the new map.
+    pub added: Vec<Arc<RelayNode>>,
+    /// Nodes only present in the old map.
+    pub removed: Vec<Arc<RelayNode>>,
+    /// Nodes present in both maps with a different configuration, as `(old, new)`.
+    pub changed: Vec<(Arc<RelayNode>, Arc<RelayNode>)>,
+}
+
+impl RelayMapDiff {
+    /// Returns `true` if the maps are identical.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Builder for a [`RelayMap`], see [`RelayMap::builder`].
+#[derive(Debug, Default, Clone)]
+pub struct RelayMapBuilder {
+    nodes: Vec<RelayNode>,
+}
+
+impl RelayMapBuilder {
+    /// Adds a relay node with the given url and returns a builder to configure it.
+    ///
+    /// The node uses the [`DEFAULT_STUN_PORT`] and the default [`QuicConfig`].
+    pub fn node(self, url: RelayUrl) -> RelayNodeBuilder {
+        RelayNodeBuilder {
+            map: self,
+            node: RelayNode {
+                url,
+                stun_only: false,
+                stun_port: DEFAULT_STUN_PORT,
+                quic: Some(QuicConfig::default()),
+            },
+        }
+    }
+
+    /// Builds the [`RelayMap`].
+    ///
+    /// Fails if the same url was added more than once.
+    pub fn build(self) -> Result<RelayMap> {
+        RelayMap::from_nodes(self.nodes)
+    }
+}
+
+/// Builder for a single [`RelayNode`] of a [`RelayMapBuilder`].
+#[derive(Debug, Clone)]
+pub struct RelayNodeBuilder {
+    map: RelayMapBuilder,
+    node: RelayNode,
+}
+
+impl RelayNodeBuilder {
+    /// Sets the STUN port of this relay node.
+    pub fn stun_port(mut self, port: u16) -> Self {
+        self.node.stun_port = port;
+        self
+    }
+
+    /// Sets whether this relay node should only be used for STUN requests.
+    pub fn stun_only(mut self, stun_only: bool) -> Self {
+        self.node.stun_only = stun_only;
+        self
+    }
+
+    /// Sets the QUIC address discovery configuration, `None` disables it.
+    pub fn quic(mut self, quic: Option<QuicConfig>) -> Self {
+        self.node.quic = quic;
+        self
+    }
+
+    /// Finishes this relay node and adds another one with the given url.
+    pub fn node(self, url: RelayUrl) -> RelayNodeBuilder {
+        self.finish().node(url)
+    }
+
+    /// Finishes this relay node, returning the [`RelayMapBuilder`].
+    pub fn finish(self) -> RelayMapBuilder {
+        let Self { mut map, node } = self;
+        map.nodes.push(node);
+        map
+    }
+
+    /// Finishes this relay node and builds the [`RelayMap`].
+    pub fn build(self) -> Result<RelayMap> {
+        self.finish().build()
+    }
 }
 
 impl fmt::Display for RelayMap {
@@ -165,3 +336,81 @@ impl fmt::Display for RelayNode {
         write!(f, "{}", self.url)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder() {
+        let url1: RelayUrl = "https://relay1.example.com".parse().unwrap();
+        let url2: RelayUrl = "https://relay2.example.com".parse().unwrap();
+        let map = RelayMap::builder()
+            .node(url1.clone())
+            .node(url2.clone())
+            .stun_port(1234)
+            .quic(None)
+            .build()
+            .unwrap();
+        assert_eq!(map.len(), 2);
+        assert_eq!(
+            map.get_node(&url1).unwrap().as_ref(),
+            &RelayNode {
+                url: url1.clone(),
+                stun_only: false,
+                stun_port: DEFAULT_STUN_PORT,
+                quic: Some(QuicConfig::default()),
+            }
+        );
+        let node2 = map.get_node(&url2).unwrap();
+        assert_eq!(node2.stun_port, 1234);
+        assert_eq!(node2.quic, None);
+
+        assert!(RelayMap::builder()
+            .node(url1.clone())
+            .node(url1)
+            .build()
+            .is_err());
+        assert!(RelayMap::builder().build().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_merge_diff() {
+        let url1: RelayUrl = "https://relay1.example.com".parse().unwrap();
+        let url2: RelayUrl = "https://relay2.example.com".parse().unwrap();
+        let url3: RelayUrl = "https://relay3.example.com".parse().unwrap();
+        let a = RelayMap::builder()
+            .node(url1.clone())
+            .node(url2.clone())
+            .build()
+            .unwrap();
+        let b = RelayMap::builder()
+            .node(url2.clone())
+            .stun_port(1234)
+            .node(url3.clone())
+            .build()
+            .unwrap();
+
+        let merged = a.merge(&b, MergePolicy::KeepExisting).unwrap();
+        assert_eq!(merged.urls().collect::<Vec<_>>(), [&url1, &url2, &url3]);
+        assert_eq!(merged.get_node(&url2).unwrap().stun_port, DEFAULT_STUN_PORT);
+
+        let merged = a.merge(&b, MergePolicy::Replace).unwrap();
+        assert_eq!(merged.get_node(&url2).unwrap().stun_port, 1234);
+
+        assert!(a.merge(&b, MergePolicy::Error).is_err());
+        assert_eq!(a.merge(&a, MergePolicy::Error).unwrap(), a);
+
+        let diff = a.diff(&b);
+        assert_eq!(diff.added, [b.get_node(&url3).unwrap().clone()]);
+        assert_eq!(diff.removed, [a.get_node(&url1).unwrap().clone()]);
+        assert_eq!(
+            diff.changed,
+            [(
+                a.get_node(&url2).unwrap().clone(),
+                b.get_node(&url2).unwrap().clone()
+            )]
+        );
+        assert!(a.diff(&a).is_empty());
+    }
+}
@@ -73,3 +73,30 @@ pub enum Error {
     #[error("verification failed: {_0}")]
     Verify(&'static str),
 }
+
+#[cfg(all(test, feature = "key"))]
+mod proptests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    proptest! {
+        // Tickets are pasted in from untrusted sources, decoding them must fail with an
+        // error, never panic.
+        #[test]
+        fn node_ticket_arbitrary_bytes(bytes in prop::collection::vec(any::<u8>(), 0..256)) {
+            let _ = NodeTicket::from_bytes(&bytes);
+        }
+
+        #[test]
+        fn blob_ticket_arbitrary_bytes(bytes in prop::collection::vec(any::<u8>(), 0..256)) {
+            let _ = BlobTicket::from_bytes(&bytes);
+        }
+
+        #[test]
+        fn ticket_arbitrary_str(s in "(node|blob)[a-z2-7]{0,160}") {
+            let _ = NodeTicket::deserialize(&s);
+            let _ = BlobTicket::deserialize(&s);
+        }
+    }
+}
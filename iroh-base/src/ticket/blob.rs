@@ -27,11 +27,24 @@ pub struct BlobTicket {
 /// Wire format for [`BlobTicket`].
 ///
 /// In the future we might have multiple variants (not versions, since they
-/// might be both equally valid), so this is a single variant enum to force
-/// postcard to add a discriminator.
+/// might be both equally valid), so this is an enum to force postcard to add
+/// a discriminator.
 #[derive(Serialize, Deserialize)]
 enum TicketWireFormat {
     Variant0(BlobTicket),
+    /// Same as `Variant0`, but the hash is a self describing multihash.
+    ///
+    /// Only decoded for now, so that readers already understand it once tickets are
+    /// created with hash algorithms other than BLAKE3.
+    Variant1(MultihashBlobTicket),
+}
+
+/// A [`BlobTicket`] with the hash encoded using [`Hash::to_multihash`].
+#[derive(Serialize, Deserialize)]
+struct MultihashBlobTicket {
+    node: NodeAddr,
+    format: BlobFormat,
+    hash: Vec<u8>,
 }
 
 impl Ticket for BlobTicket {
@@ -44,8 +57,14 @@ impl Ticket for BlobTicket {
 
     fn from_bytes(bytes: &[u8]) -> std::result::Result<Self, ticket::Error> {
         let res: TicketWireFormat = postcard::from_bytes(bytes).map_err(ticket::Error::Postcard)?;
-        let TicketWireFormat::Variant0(res) = res;
-        Ok(res)
+        match res {
+            TicketWireFormat::Variant0(res) => Ok(res),
+            TicketWireFormat::Variant1(MultihashBlobTicket { node, format, hash }) => {
+                let hash = Hash::from_multihash(&hash)
+                    .map_err(|_| ticket::Error::Verify("invalid multihash"))?;
+                Ok(Self { node, format, hash })
+            }
+        }
     }
 }
 
@@ -178,4 +197,37 @@ mod tests {
         ").unwrap();
         assert_eq_hex!(base32, expected);
     }
+
+    #[test]
+    fn test_ticket_multihash_variant() {
+        let bytes = parse_hexdump(
+            "
+            01 # discriminator for variant 1
+            ae58ff8833241ac82d6ff7611046ed67b5072d142c588d0063e942d9a75502b6 # node id, 32 bytes
+            00 # relay url
+            00 # number of addresses (0)
+            00 # format (raw)
+            22 # multihash length (34)
+            1e # blake3
+            20 # digest length (32)
+            0b84d358e4c8be6c38626b2182ff575818ba6bd3f4b90464994be14cb354a072 # hash, 32 bytes
+        ",
+        )
+        .unwrap();
+        let ticket = BlobTicket::from_bytes(&bytes).unwrap();
+        assert_eq!(
+            ticket.hash(),
+            Hash::from_str("0b84d358e4c8be6c38626b2182ff575818ba6bd3f4b90464994be14cb354a072")
+                .unwrap()
+        );
+        assert_eq!(ticket.format(), BlobFormat::Raw);
+
+        // sha2-256 is not supported
+        let mut sha = bytes.clone();
+        sha[37] = 0x12;
+        assert!(matches!(
+            BlobTicket::from_bytes(&sha),
+            Err(ticket::Error::Verify(_))
+        ));
+    }
 }
@@ -43,7 +43,7 @@ use tracing::{debug, error, event, info_span, trace, warn, Instrument, Level};
 use url::Url;
 
 use crate::{
-    defaults::timeouts::*,
+    defaults::{timeouts::*, DEFAULT_KEEPALIVE_INTERVAL, DEFAULT_KEEPALIVE_TIMEOUT},
     http::{Protocol, RELAY_PATH},
     protos::relay::DerpCodec,
     RelayUrl,
@@ -121,6 +121,7 @@ pub enum ClientError {
 #[derive(Clone, Debug)]
 pub struct Client {
     inner: mpsc::Sender<ActorMessage>,
+    priority: mpsc::Sender<PrioritySend>,
     public_key: PublicKey,
     #[allow(dead_code)]
     recv_loop: Arc<AbortOnDropHandle<()>>,
@@ -139,6 +140,9 @@ enum ActorMessage {
     IsConnected(oneshot::Sender<Result<bool, ClientError>>),
 }
 
+/// A packet sent with [`Client::send_priority`], bypassing the regular actor inbox.
+type PrioritySend = (PublicKey, Bytes, oneshot::Sender<Result<(), ClientError>>);
+
 /// Receiving end of a [`Client`].
 #[derive(Debug)]
 pub struct ClientReceiver {
@@ -161,6 +165,10 @@ struct Actor {
     ping_tasks: JoinSet<()>,
     dns_resolver: DnsResolver,
     proxy_url: Option<Url>,
+    keepalive_interval: Option<Duration>,
+    keepalive_timeout: Duration,
+    /// The keepalive ping we are waiting for a pong for, and when it was sent.
+    keepalive_ping: Option<([u8; 8], Instant)>,
 }
 
 #[derive(Default, Debug)]
@@ -207,6 +215,10 @@ pub struct ClientBuilder {
     insecure_skip_cert_verify: bool,
     /// HTTP Proxy
     proxy_url: Option<Url>,
+    /// Default is [`DEFAULT_KEEPALIVE_INTERVAL`]
+    keepalive_interval: Option<Duration>,
+    /// Default is [`DEFAULT_KEEPALIVE_TIMEOUT`]
+    keepalive_timeout: Duration,
 }
 
 impl ClientBuilder {
@@ -222,6 +234,8 @@ impl ClientBuilder {
             #[cfg(any(test, feature = "test-utils"))]
             insecure_skip_cert_verify: false,
             proxy_url: None,
+            keepalive_interval: Some(DEFAULT_KEEPALIVE_INTERVAL),
+            keepalive_timeout: DEFAULT_KEEPALIVE_TIMEOUT,
         }
     }
 
@@ -281,6 +295,27 @@ impl ClientBuilder {
         self
     }
 
+    /// Sets the interval at which keepalive pings are sent to the relay server.
+    ///
+    /// If the server does not answer a keepalive ping within the
+    /// [keepalive timeout](Self::keepalive_timeout), the connection is considered dead and
+    /// the client reconnects.  This detects connections silently dropped by e.g. a NAT much
+    /// sooner than waiting for the read timeout.
+    ///
+    /// `None` disables keepalive pings.  Defaults to 30 seconds.
+    pub fn keepalive_interval(mut self, interval: Option<Duration>) -> Self {
+        self.keepalive_interval = interval;
+        self
+    }
+
+    /// Sets how long to wait for the pong to a keepalive ping before reconnecting.
+    ///
+    /// Defaults to 5 seconds.
+    pub fn keepalive_timeout(mut self, timeout: Duration) -> Self {
+        self.keepalive_timeout = timeout;
+        self
+    }
+
     /// Build the [`Client`]
     pub fn build(self, key: SecretKey, dns_resolver: DnsResolver) -> (Client, ClientReceiver) {
         // TODO: review TLS config
@@ -320,18 +355,24 @@ impl ClientBuilder {
             tls_connector,
             dns_resolver,
             proxy_url: self.proxy_url,
+            keepalive_interval: self.keepalive_interval,
+            keepalive_timeout: self.keepalive_timeout,
+            keepalive_ping: None,
         };
 
         let (msg_sender, inbox) = mpsc::channel(64);
+        let (priority_sender, priority_inbox) = mpsc::channel(64);
         let (s, r) = mpsc::channel(64);
         let recv_loop = tokio::task::spawn(
-            async move { inner.run(inbox, s).await }.instrument(info_span!("client")),
+            async move { inner.run(inbox, priority_inbox, s).await }
+                .instrument(info_span!("client")),
         );
 
         (
             Client {
                 public_key,
                 inner: msg_sender,
+                priority: priority_sender,
                 recv_loop: Arc::new(AbortOnDropHandle::new(recv_loop)),
             },
             ClientReceiver { msg_receiver: r },
@@ -447,6 +488,23 @@ impl Client {
         self.send_actor(|s| ActorMessage::Send(dst_key, b, s)).await
     }
 
+    /// Send a packet to the server, ahead of any packets queued with [`Client::send`].
+    ///
+    /// This is meant for small control packets, like disco pings and call-me-maybe
+    /// messages, which must get through even while bulk data is saturating the relay
+    /// connection.
+    ///
+    /// If there is an error sending the packet, it closes the underlying relay connection before
+    /// returning.
+    pub async fn send_priority(&self, dst_key: PublicKey, b: Bytes) -> Result<(), ClientError> {
+        let (s, r) = oneshot::channel();
+        self.priority
+            .send((dst_key, b, s))
+            .await
+            .map_err(|_| ClientError::ActorGone)?;
+        r.await.map_err(|_| ClientError::ActorGone)?
+    }
+
     /// Close the http relay connection.
     pub async fn close(self) -> Result<(), ClientError> {
         self.send_actor(ActorMessage::Close).await
@@ -467,6 +525,7 @@ impl Actor {
     async fn run(
         mut self,
         mut inbox: mpsc::Receiver<ActorMessage>,
+        mut priority_inbox: mpsc::Receiver<PrioritySend>,
         msg_sender: mpsc::Sender<Result<ReceivedMessage, ClientError>>,
     ) {
         // Add an initial connection attempt.
@@ -474,10 +533,33 @@ impl Actor {
             msg_sender.send(Err(err)).await.ok();
         }
 
+        let mut keepalive = self.keepalive_interval.map(|period| {
+            let mut interval = tokio::time::interval_at(Instant::now() + period, period);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            interval
+        });
+
         loop {
+            let keepalive_deadline = self
+                .keepalive_ping
+                .map(|(_, sent)| sent + self.keepalive_timeout);
             tokio::select! {
+                // A frame which arrived before the keepalive deadline must be seen first.
+                biased;
                 res = self.recv_detail() => {
+                    if res.is_ok() {
+                        // Any frame shows the connection is alive, the pong may just be stuck
+                        // behind a lot of data.
+                        if let Some((_, sent)) = self.keepalive_ping.as_mut() {
+                            *sent = Instant::now();
+                        }
+                    }
                     if let Ok(ReceivedMessage::Pong(ping)) = res {
+                        if self.keepalive_ping.is_some_and(|(data, _)| data == ping) {
+                            trace!("keepalive pong");
+                            self.keepalive_ping = None;
+                            continue;
+                        }
                         match self.pings.unregister(ping, "pong") {
                             Some(chan) => {
                                 if chan.send(()).is_err() {
@@ -492,6 +574,20 @@ impl Actor {
                     }
                     msg_sender.send(res).await.ok();
                 }
+                _ = async { keepalive.as_mut().expect("checked").tick().await }, if keepalive.is_some() => {
+                    self.send_keepalive().await;
+                }
+                _ = async { tokio::time::sleep_until(keepalive_deadline.expect("checked")).await }, if keepalive_deadline.is_some() => {
+                    warn!("no pong for keepalive ping, connection is dead, reconnecting");
+                    self.close_for_reconnect().await;
+                    if let Err(err) = self.connect("keepalive").await {
+                        msg_sender.send(Err(err)).await.ok();
+                    }
+                }
+                Some((key, data, s)) = priority_inbox.recv() => {
+                    let res = self.send_priority(key, data).await;
+                    s.send(res).ok();
+                }
                 msg = inbox.recv() => {
                     let Some(msg) = msg else {
                         // Shutting down
@@ -499,6 +595,13 @@ impl Actor {
                         break;
                     };
 
+                    // Priority packets which queued up in the meantime must not wait
+                    // behind this message.
+                    while let Ok((key, data, s)) = priority_inbox.try_recv() {
+                        let res = self.send_priority(key, data).await;
+                        s.send(res).ok();
+                    }
+
                     match msg {
                         ActorMessage::Connect(s) => {
                             let res = self.connect("actor msg").await.map(|(client, _)| (client));
@@ -785,6 +888,26 @@ impl Actor {
         });
     }
 
+    /// Sends a keepalive ping on the current connection.
+    ///
+    /// Does nothing if there is no connection or a keepalive ping is still in flight.
+    async fn send_keepalive(&mut self) {
+        if self.keepalive_ping.is_some() {
+            return;
+        }
+        let Some((conn, _)) = self.relay_conn.as_ref() else {
+            return;
+        };
+        let data = rand::thread_rng().gen::<[u8; 8]>();
+        trace!("keepalive ping: {}", hex::encode(data));
+        if conn.send_priority_ping(data).await.is_err() {
+            warn!("failed to send keepalive ping");
+            self.close_for_reconnect().await;
+            return;
+        }
+        self.keepalive_ping = Some((data, Instant::now()));
+    }
+
     async fn send(&mut self, remote_node: NodeId, payload: Bytes) -> Result<(), ClientError> {
         trace!(remote_node = %remote_node.fmt_short(), len = payload.len(), "send");
         let (conn, _) = self.connect("send").await?;
@@ -795,6 +918,20 @@ impl Actor {
         Ok(())
     }
 
+    async fn send_priority(
+        &mut self,
+        remote_node: NodeId,
+        payload: Bytes,
+    ) -> Result<(), ClientError> {
+        trace!(remote_node = %remote_node.fmt_short(), len = payload.len(), "send priority");
+        let (conn, _) = self.connect("send_priority").await?;
+        if conn.send_priority(remote_node, payload).await.is_err() {
+            self.close_for_reconnect().await;
+            return Err(ClientError::Send);
+        }
+        Ok(())
+    }
+
     async fn send_pong(&mut self, data: [u8; 8]) -> Result<(), ClientError> {
         debug!("send_pong");
         let (conn, _) = self.connect("send_pong").await?;
@@ -1007,6 +1144,7 @@ impl Actor {
     /// requires a connection, it will call `connect`.
     async fn close_for_reconnect(&mut self) {
         debug!("close for reconnect");
+        self.keepalive_ping = None;
         if let Some((conn, _)) = self.relay_conn.take() {
             conn.close().await
         }
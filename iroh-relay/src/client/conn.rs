@@ -31,7 +31,8 @@ use crate::{
     client::streams::{MaybeTlsStreamReader, MaybeTlsStreamWriter},
     defaults::timeouts::CLIENT_RECV_TIMEOUT,
     protos::relay::{
-        write_frame, ClientInfo, DerpCodec, Frame, MAX_PACKET_SIZE, PER_CLIENT_READ_QUEUE_DEPTH,
+        write_frame, ClientInfo, DerpCodec, Frame, MAX_PACKET_SIZE,
+        PER_CLIENT_PRIORITY_SEND_QUEUE_DEPTH, PER_CLIENT_READ_QUEUE_DEPTH,
         PER_CLIENT_SEND_QUEUE_DEPTH, PROTOCOL_VERSION,
     },
 };
@@ -87,6 +88,8 @@ pub struct ConnTasks {
     /// Channel on which to communicate to the server. The associated [`mpsc::Receiver`] will close
     /// if there is ever an error writing to the server.
     writer_channel: mpsc::Sender<ConnWriterMessage>,
+    /// Channel for messages which are written ahead of anything queued on `writer_channel`.
+    priority_channel: mpsc::Sender<ConnWriterMessage>,
     /// JoinHandle for the [`ConnWriter`] task
     writer_task: AbortOnDropHandle<Result<()>>,
     reader_task: AbortOnDropHandle<()>,
@@ -106,6 +109,33 @@ impl Conn {
         Ok(())
     }
 
+    /// Sends a packet to the node identified by `dst`, ahead of any queued packets.
+    ///
+    /// This is meant for small control packets, such as disco pings and call-me-maybe
+    /// messages, which must not wait behind bulk data saturating the connection.
+    ///
+    /// Errors if the packet is larger than [`MAX_PACKET_SIZE`]
+    pub async fn send_priority(&self, dst: NodeId, packet: Bytes) -> Result<()> {
+        trace!(%dst, len = packet.len(), "[RELAY] send priority");
+
+        self.inner
+            .priority_channel
+            .send(ConnWriterMessage::Packet((dst, packet)))
+            .await?;
+        Ok(())
+    }
+
+    /// Send a ping with 8 bytes of random data, ahead of any queued packets.
+    ///
+    /// Used for keepalive pings, whose pong must not be delayed by a backlog of packets.
+    pub async fn send_priority_ping(&self, data: [u8; 8]) -> Result<()> {
+        self.inner
+            .priority_channel
+            .send(ConnWriterMessage::Ping(data))
+            .await?;
+        Ok(())
+    }
+
     /// Send a ping with 8 bytes of random data.
     pub async fn send_ping(&self, data: [u8; 8]) -> Result<()> {
         self.inner
@@ -225,14 +255,25 @@ enum ConnWriterMessage {
 ///
 /// Shutsdown when you send a [`ConnWriterMessage::Shutdown`], or if there is an error writing to
 /// the server.
+///
+/// Messages on `recv_priority` are always written before any pending message on `recv_msgs`.
 struct ConnWriterTasks {
     recv_msgs: mpsc::Receiver<ConnWriterMessage>,
+    recv_priority: mpsc::Receiver<ConnWriterMessage>,
     writer: ConnWriter,
 }
 
 impl ConnWriterTasks {
     async fn run(mut self) -> Result<()> {
-        while let Some(msg) = self.recv_msgs.recv().await {
+        loop {
+            let msg = tokio::select! {
+                biased;
+                Some(msg) = self.recv_priority.recv() => Some(msg),
+                msg = self.recv_msgs.recv() => msg,
+            };
+            let Some(msg) = msg else {
+                break;
+            };
             match msg {
                 ConnWriterMessage::Packet((key, bytes)) => {
                     send_packet(&mut self.writer, key, bytes).await?;
@@ -377,10 +418,12 @@ impl ConnBuilder {
 
         // create task to handle writing to the server
         let (writer_sender, writer_recv) = mpsc::channel(PER_CLIENT_SEND_QUEUE_DEPTH);
+        let (priority_sender, priority_recv) = mpsc::channel(PER_CLIENT_PRIORITY_SEND_QUEUE_DEPTH);
         let writer_task = tokio::task::spawn(
             ConnWriterTasks {
                 writer: self.writer,
                 recv_msgs: writer_recv,
+                recv_priority: priority_recv,
             }
             .run()
             .instrument(info_span!("conn.writer")),
@@ -426,6 +469,7 @@ impl ConnBuilder {
             inner: Arc::new(ConnTasks {
                 local_addr: self.local_addr,
                 writer_channel: writer_sender,
+                priority_channel: priority_sender,
                 writer_task: AbortOnDropHandle::new(writer_task),
                 reader_task: AbortOnDropHandle::new(reader_task),
             }),
@@ -506,3 +550,75 @@ pub(crate) async fn send_packet<S: Sink<Frame, Error = std::io::Error> + Unpin>(
 
     Ok(())
 }
+
+#[cfg(all(test, feature = "server"))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_priority_packets_skip_queue() -> Result<()> {
+        let _guard = iroh_test::logging::setup();
+
+        // A tiny buffer, so that the writer blocks on the first bulk packet until we read.
+        let (client, server) = tokio::io::duplex(64);
+        let (_client_reader, client_writer) = tokio::io::split(client);
+        let writer = ConnWriter::Derp(FramedWrite::new(
+            MaybeTlsStreamWriter::Mem(client_writer),
+            DerpCodec,
+        ));
+        let (writer_sender, writer_recv) = mpsc::channel(PER_CLIENT_SEND_QUEUE_DEPTH);
+        let (priority_sender, priority_recv) = mpsc::channel(PER_CLIENT_PRIORITY_SEND_QUEUE_DEPTH);
+        let writer_task = AbortOnDropHandle::new(tokio::task::spawn(
+            ConnWriterTasks {
+                writer,
+                recv_msgs: writer_recv,
+                recv_priority: priority_recv,
+            }
+            .run(),
+        ));
+
+        const BULK: usize = 10;
+        let dst = SecretKey::generate().public();
+        for _ in 0..BULK {
+            writer_sender
+                .send(ConnWriterMessage::Packet((
+                    dst,
+                    Bytes::from(vec![0u8; 1000]),
+                )))
+                .await?;
+        }
+        priority_sender
+            .send(ConnWriterMessage::Packet((
+                dst,
+                Bytes::from_static(b"priority"),
+            )))
+            .await?;
+
+        let mut server = FramedRead::new(server, DerpCodec);
+        let mut priority_pos = None;
+        for pos in 0..=BULK {
+            let frame = tokio::time::timeout(Duration::from_secs(5), server.next())
+                .await?
+                .expect("stream ended")?;
+            let (dst_key, packet) = match frame {
+                Frame::SendPacket { dst_key, packet } => (dst_key, packet),
+                frame => bail!("unexpected frame: {frame:?}"),
+            };
+            assert_eq!(dst_key, dst);
+            if &packet[..] == b"priority" {
+                priority_pos = Some(pos);
+            }
+        }
+
+        // At most the bulk packet which was already being written goes first.
+        let priority_pos = priority_pos.expect("priority packet not written");
+        assert!(
+            priority_pos <= 1,
+            "priority packet written at {priority_pos}"
+        );
+
+        writer_sender.send(ConnWriterMessage::Shutdown).await?;
+        writer_task.await??;
+        Ok(())
+    }
+}
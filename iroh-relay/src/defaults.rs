@@ -1,5 +1,7 @@
 //! Default values used in the relay.
 
+use std::time::Duration;
+
 pub use iroh_base::relay_map::{DEFAULT_RELAY_QUIC_PORT, DEFAULT_STUN_PORT};
 
 /// The default HTTP port used by the Relay server.
@@ -11,6 +13,12 @@ pub const DEFAULT_HTTPS_PORT: u16 = 443;
 /// The default metrics port used by the Relay server.
 pub const DEFAULT_METRICS_PORT: u16 = 9090;
 
+/// The default interval at which the relay client sends keepalive pings to the relay server.
+pub const DEFAULT_KEEPALIVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// The default time the relay client waits for the pong to a keepalive ping.
+pub const DEFAULT_KEEPALIVE_TIMEOUT: Duration = timeouts::PING_TIMEOUT;
+
 /// Contains all timeouts that we use in `iroh`.
 pub(crate) mod timeouts {
     use std::time::Duration;
@@ -48,6 +48,11 @@ pub(crate) const SERVER_CHANNEL_SIZE: usize = 1024 * 100;
 /// The number of packets buffered for sending per client
 pub(crate) const PER_CLIENT_SEND_QUEUE_DEPTH: usize = 512; //32;
 pub(crate) const PER_CLIENT_READ_QUEUE_DEPTH: usize = 512;
+/// The number of priority packets buffered for sending per client
+///
+/// These are small control packets (disco pings, call-me-maybe) which jump ahead of the
+/// regular send queue.
+pub(crate) const PER_CLIENT_PRIORITY_SEND_QUEUE_DEPTH: usize = 64;
 
 /// ProtocolVersion is bumped whenever there's a wire-incompatible change.
 ///  - version 1 (zero on wire): consistent box headers, in use by employee dev nodes a bit
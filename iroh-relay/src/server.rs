@@ -247,10 +247,13 @@ impl Server {
             debug!("Starting metrics server");
             use iroh_metrics::core::Metric;
 
-            iroh_metrics::core::Core::init(|reg, metrics| {
+            // Another server in this process may already have set up the metrics.
+            if let Err(err) = iroh_metrics::core::Core::try_init(|reg, metrics| {
                 metrics.insert(metrics::Metrics::new(reg));
                 metrics.insert(StunMetrics::new(reg));
-            });
+            }) {
+                debug!("metrics already initialized: {err:#}");
+            }
             tasks.spawn(
                 iroh_metrics::metrics::start_metrics_server(addr)
                     .instrument(info_span!("metrics-server")),
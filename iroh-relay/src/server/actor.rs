@@ -157,6 +157,7 @@ impl Actor {
                 } else {
                     warn!(?dst, "no way to reach client, dropped packet");
                     inc!(Metrics, send_packets_dropped);
+                    inc!(Metrics, packets_dropped_no_client);
                 }
             }
             Message::SendDiscoPacket { dst, data, src } => {
@@ -179,6 +180,7 @@ impl Actor {
                 } else {
                     warn!(?dst, "disco: no way to reach client, dropped packet");
                     inc!(Metrics, disco_packets_dropped);
+                    inc!(Metrics, packets_dropped_no_client);
                 }
             }
             Message::CreateClient(client_builder) => {
@@ -339,4 +341,67 @@ mod tests {
         server_task.await??;
         Ok(())
     }
+
+    #[cfg(feature = "metrics")]
+    #[tokio::test]
+    async fn test_packets_dropped_metrics() -> Result<()> {
+        use iroh_metrics::core::{Core, Metric};
+
+        let _ = Core::try_init(|reg, metrics| {
+            metrics.insert(Metrics::new(reg));
+            metrics.insert(crate::server::metrics::StunMetrics::new(reg));
+        });
+        let metrics = Core::get()
+            .and_then(|core| core.get_collector::<Metrics>())
+            .expect("relay metrics are registered");
+
+        let (server_channel, mut server_channel_r) = mpsc::channel(20);
+        let (_, actor_r) = mpsc::channel(1);
+        let mut actor = Actor::new(actor_r);
+        let node_id_a = SecretKey::generate().public();
+        let node_id_b = SecretKey::generate().public();
+        let data = Bytes::from_static(b"hello world!");
+
+        // Other tests share the global counters, so only check for a lower bound.
+        let no_client = metrics.packets_dropped_no_client.get();
+        actor
+            .handle_message(Message::SendPacket {
+                dst: node_id_b,
+                data: data.clone(),
+                src: node_id_a,
+            })
+            .await;
+        actor
+            .handle_message(Message::SendDiscoPacket {
+                dst: node_id_b,
+                data: data.clone(),
+                src: node_id_a,
+            })
+            .await;
+        assert!(metrics.packets_dropped_no_client.get() >= no_client + 2);
+
+        // connect b, then close its connection without telling the actor
+        let (client_b, b_io) = test_client_builder(node_id_b, server_channel);
+        actor.handle_message(Message::CreateClient(client_b)).await;
+        drop(b_io);
+        // the connection task reports removal once its send queues are gone
+        match server_channel_r.recv().await {
+            Some(Message::RemoveClient { node_id, .. }) => assert_eq!(node_id, node_id_b),
+            msg => panic!("expected RemoveClient, got {msg:?}"),
+        }
+
+        let client_gone = metrics.packets_dropped_client_gone.get();
+        actor
+            .handle_message(Message::SendPacket {
+                dst: node_id_b,
+                data,
+                src: node_id_a,
+            })
+            .await;
+        assert!(metrics.packets_dropped_client_gone.get() > client_gone);
+        assert!(!actor.clients.contains_key(&node_id_b));
+
+        actor.clients.shutdown().await;
+        Ok(())
+    }
 }
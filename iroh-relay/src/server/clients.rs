@@ -124,9 +124,11 @@ impl Clients {
             Ok(_) => return Ok(()),
             Err(SendError::PacketDropped) => {
                 warn!("client {key:?} too busy to receive packet, dropping packet");
+                inc!(Metrics, packets_dropped_client_busy);
             }
             Err(SendError::SenderClosed) => {
                 warn!("Can no longer write to client {key:?}, dropping message and pruning connection");
+                inc!(Metrics, packets_dropped_client_gone);
                 self.unregister(key).await;
             }
         }
@@ -657,13 +657,24 @@ impl std::ops::DerefMut for Handlers {
 
 #[cfg(test)]
 mod tests {
-    use std::sync::Arc;
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Instant,
+    };
 
-    use anyhow::Result;
+    use anyhow::{Context, Result};
     use bytes::Bytes;
     use iroh_base::key::{PublicKey, SecretKey};
     use reqwest::Url;
-    use tokio::{sync::mpsc, task::JoinHandle};
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::tcp::{OwnedReadHalf, OwnedWriteHalf},
+        sync::mpsc,
+        task::JoinHandle,
+    };
     use tokio_util::codec::{FramedRead, FramedWrite};
     use tracing::{info, info_span, Instrument};
     use tracing_subscriber::{prelude::*, EnvFilter};
@@ -979,6 +990,22 @@ mod tests {
             }
         }
 
+        // send a priority message from a to b!
+        let msg = Bytes::from_static(b"jumping the queue");
+        client_a.send_priority(public_key_b, msg.clone()).await?;
+        match client_receiver_b.recv().await? {
+            ReceivedMessage::ReceivedPacket {
+                remote_node_id,
+                data,
+            } => {
+                assert_eq!(public_key_a, remote_node_id);
+                assert_eq!(&msg[..], data);
+            }
+            msg => {
+                anyhow::bail!("expected ReceivedPacket msg, got {msg:?}");
+            }
+        }
+
         // close the server and clients
         server_task.close().await;
 
@@ -1120,4 +1147,212 @@ mod tests {
         assert!(new_client_receiver_b.recv().await.is_err());
         Ok(())
     }
+
+    /// A TCP proxy which can silently stop forwarding on its existing connections.
+    ///
+    /// This behaves like a NAT which dropped its mapping: the connection stays open from
+    /// the client's point of view, but nothing gets through anymore.
+    struct StallingProxy {
+        addr: SocketAddr,
+        /// Number of accepted connections.
+        accepted: Arc<AtomicUsize>,
+        /// Connections accepted before this count no longer forward any data.
+        stalled: Arc<AtomicUsize>,
+        _task: AbortOnDropHandle<()>,
+    }
+
+    impl StallingProxy {
+        async fn spawn(target: SocketAddr) -> Result<Self> {
+            let listener = TcpListener::bind("127.0.0.1:0").await?;
+            let addr = listener.local_addr()?;
+            let accepted = Arc::new(AtomicUsize::new(0));
+            let stalled = Arc::new(AtomicUsize::new(0));
+            let task = tokio::spawn({
+                let accepted = accepted.clone();
+                let stalled = stalled.clone();
+                async move {
+                    while let Ok((client, _)) = listener.accept().await {
+                        let idx = accepted.fetch_add(1, Ordering::SeqCst);
+                        let Ok(server) = TcpStream::connect(target).await else {
+                            continue;
+                        };
+                        let (client_read, client_write) = client.into_split();
+                        let (server_read, server_write) = server.into_split();
+                        tokio::spawn(Self::forward(
+                            client_read,
+                            server_write,
+                            idx,
+                            stalled.clone(),
+                        ));
+                        tokio::spawn(Self::forward(
+                            server_read,
+                            client_write,
+                            idx,
+                            stalled.clone(),
+                        ));
+                    }
+                }
+            });
+            Ok(Self {
+                addr,
+                accepted,
+                stalled,
+                _task: AbortOnDropHandle::new(task),
+            })
+        }
+
+        async fn forward(
+            mut from: OwnedReadHalf,
+            mut to: OwnedWriteHalf,
+            idx: usize,
+            stalled: Arc<AtomicUsize>,
+        ) {
+            let mut buf = vec![0u8; 16 * 1024];
+            loop {
+                let n = match from.read(&mut buf).await {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => n,
+                };
+                if idx < stalled.load(Ordering::SeqCst) {
+                    continue;
+                }
+                if to.write_all(&buf[..n]).await.is_err() {
+                    break;
+                }
+            }
+        }
+
+        fn accepted(&self) -> usize {
+            self.accepted.load(Ordering::SeqCst)
+        }
+
+        /// Silently drops everything on the connections accepted so far.
+        fn stall_existing(&self) {
+            self.stalled.store(self.accepted(), Ordering::SeqCst);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_client_keepalive() -> Result<()> {
+        let _guard = iroh_test::logging::setup();
+
+        let server = ServerBuilder::new("127.0.0.1:0".parse().unwrap())
+            .spawn()
+            .await?;
+        let proxy = StallingProxy::spawn(server.addr()).await?;
+        let url: Url = format!("http://{}", proxy.addr).parse()?;
+
+        // A timeout this short reconnects quickly if a pong is not handled.
+        let (client, mut client_receiver) = ClientBuilder::new(url)
+            .keepalive_interval(Some(Duration::from_millis(50)))
+            .keepalive_timeout(Duration::from_millis(100))
+            .build(
+                SecretKey::generate(),
+                crate::dns::default_resolver().clone(),
+            );
+        client.connect().await?;
+        assert_eq!(proxy.accepted(), 1);
+
+        // Several keepalive pings go out and get answered, without the pongs being
+        // surfaced to the receiver or the connection being torn down.
+        let res = tokio::time::timeout(Duration::from_millis(300), client_receiver.recv()).await;
+        assert!(res.is_err(), "unexpected message: {res:?}");
+        assert!(client.is_connected().await?);
+        assert_eq!(proxy.accepted(), 1);
+
+        client.close().await?;
+        server.shutdown();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_client_keepalive_busy() -> Result<()> {
+        let _guard = iroh_test::logging::setup();
+
+        let server = ServerBuilder::new("127.0.0.1:0".parse().unwrap())
+            .spawn()
+            .await?;
+        let proxy = StallingProxy::spawn(server.addr()).await?;
+        let proxy_url: Url = format!("http://{}", proxy.addr).parse()?;
+        let server_url: Url = format!("http://{}", server.addr()).parse()?;
+
+        let (client_a, _client_receiver_a) = ClientBuilder::new(proxy_url)
+            .keepalive_interval(Some(Duration::from_millis(50)))
+            .keepalive_timeout(Duration::from_millis(100))
+            .build(
+                SecretKey::generate(),
+                crate::dns::default_resolver().clone(),
+            );
+        client_a.connect().await?;
+        let key_b = SecretKey::generate();
+        let public_key_b = key_b.public();
+        let (client_b, mut client_receiver_b) =
+            ClientBuilder::new(server_url).build(key_b, crate::dns::default_resolver().clone());
+        client_b.connect().await?;
+        let _drain_b = AbortOnDropHandle::new(tokio::spawn(async move {
+            while let Some(Ok(_)) = client_receiver_b.recv().await {}
+        }));
+
+        // Keep the writer queue full for many keepalive intervals.  The keepalive pings
+        // must not wait behind the queued packets, or their pongs arrive too late.
+        let payload = Bytes::from(vec![0u8; 16 * 1024]);
+        let start = Instant::now();
+        let mut sent = 0;
+        while start.elapsed() < Duration::from_secs(1) {
+            client_a.send(public_key_b, payload.clone()).await?;
+            sent += 1;
+        }
+        info!("sent {sent} packets");
+
+        assert!(client_a.is_connected().await?);
+        assert_eq!(proxy.accepted(), 1, "client reconnected");
+
+        client_a.close().await?;
+        client_b.close().await?;
+        server.shutdown();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_client_keepalive_reconnect() -> Result<()> {
+        let _guard = iroh_test::logging::setup();
+
+        let server = ServerBuilder::new("127.0.0.1:0".parse().unwrap())
+            .spawn()
+            .await?;
+        let proxy = StallingProxy::spawn(server.addr()).await?;
+        let url: Url = format!("http://{}", proxy.addr).parse()?;
+
+        let interval = Duration::from_millis(100);
+        let timeout = Duration::from_millis(200);
+        let (client, _client_receiver) = ClientBuilder::new(url)
+            .keepalive_interval(Some(interval))
+            .keepalive_timeout(timeout)
+            .build(
+                SecretKey::generate(),
+                crate::dns::default_resolver().clone(),
+            );
+        client.connect().await?;
+        assert_eq!(proxy.accepted(), 1);
+
+        // The connection is silently dropped, only the missing pong can tell.
+        proxy.stall_existing();
+        let start = Instant::now();
+        tokio::time::timeout(interval + timeout + Duration::from_millis(500), async {
+            while proxy.accepted() < 2 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .context("client did not reconnect")?;
+        info!("reconnected after {:?}", start.elapsed());
+
+        // The new connection works.
+        let res = tokio::time::timeout(Duration::from_secs(1), client.ping()).await?;
+        assert!(res.is_ok(), "ping failed: {res:?}");
+
+        client.close().await?;
+        server.shutdown();
+        Ok(())
+    }
 }
@@ -28,6 +28,13 @@ pub struct Metrics {
     /// `FrameType::SendPacket` dropped that are disco messages
     pub disco_packets_dropped: Counter,
 
+    /// Packets dropped because the destination is not connected, disco messages included
+    pub packets_dropped_no_client: Counter,
+    /// Packets dropped because the destination's send queue is full, disco messages included
+    pub packets_dropped_client_busy: Counter,
+    /// Packets dropped because the destination's connection went away, disco messages included
+    pub packets_dropped_client_gone: Counter,
+
     /// Packets of other `FrameType`s sent
     pub other_packets_sent: Counter,
     /// Packets of other `FrameType`s received
@@ -83,6 +90,15 @@ impl Default for Metrics {
             disco_packets_sent: Counter::new("Number of disco packets sent."),
             disco_packets_recv: Counter::new("Number of disco packets received."),
             disco_packets_dropped: Counter::new("Number of disco packets dropped."),
+            packets_dropped_no_client: Counter::new(
+                "Number of packets dropped because the destination is not connected.",
+            ),
+            packets_dropped_client_busy: Counter::new(
+                "Number of packets dropped because the destination's send queue is full.",
+            ),
+            packets_dropped_client_gone: Counter::new(
+                "Number of packets dropped because the destination's connection went away.",
+            ),
 
             other_packets_sent: Counter::new(
                 "Number of packets sent that were not disco packets or 'send' packets",
@@ -1,5 +1,7 @@
 //! Default values used in [`iroh`][`crate`]
 
+use std::time::Duration;
+
 use iroh_base::relay_map::QuicConfig;
 /// The default QUIC port used by the Relay server to accept QUIC connections
 /// for QUIC address discovery
@@ -24,6 +26,9 @@ pub const DEFAULT_HTTPS_PORT: u16 = 443;
 /// The default metrics port used by the Relay server.
 pub const DEFAULT_METRICS_PORT: u16 = 9090;
 
+/// The default duration after which a QUIC handshake is logged as slow.
+pub const DEFAULT_SLOW_HANDSHAKE_THRESHOLD: Duration = Duration::from_secs(5);
+
 /// Production configuration.
 pub mod prod {
     use iroh_base::relay_map::QuicConfig;
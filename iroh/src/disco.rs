@@ -17,6 +17,15 @@
 //! message_version: u8   // (0 for now; but always ignore bytes at the end)
 //! message_payload: &[u8]
 //! ```
+//!
+//! For versions after `0` the payload starts with the length of its version `0` part, so
+//! that fields can be added while older nodes can still read the message:
+//!
+//! ```ignore
+//! v0_len:          u16  // little endian
+//! v0_payload:      [u8; v0_len]
+//! extensions:      &[u8] // ignored by nodes which don't know this version
+//! ```
 
 use std::{
     fmt::Display,
@@ -39,6 +48,9 @@ pub const MAGIC_LEN: usize = MAGIC.as_bytes().len();
 /// Current Version.
 const V0: u8 = 0;
 
+/// Length of the `v0_len` prefix of payloads with a version after [`V0`].
+const V0_LEN_LEN: usize = 2;
+
 pub(crate) const KEY_LEN: usize = 32;
 const TX_LEN: usize = 12;
 
@@ -204,8 +216,7 @@ pub struct CallMeMaybe {
 }
 
 impl Ping {
-    fn from_bytes(ver: u8, p: &[u8]) -> Result<Self> {
-        ensure!(ver == V0, "invalid version");
+    fn from_bytes(p: &[u8]) -> Result<Self> {
         // Deliberately lax on longer-than-expected messages, for future compatibility.
         ensure!(p.len() >= PING_LEN, "message too short");
         let tx_id: [u8; TX_LEN] = p[..TX_LEN].try_into().expect("length checked");
@@ -288,9 +299,9 @@ fn socket_addr_as_bytes(addr: &SocketAddr) -> [u8; EP_LENGTH] {
 }
 
 impl Pong {
-    fn from_bytes(ver: u8, p: &[u8]) -> Result<Self> {
-        ensure!(ver == V0, "invalid version");
-        let tx_id: [u8; TX_LEN] = p[..TX_LEN].try_into().context("message too short")?;
+    fn from_bytes(p: &[u8]) -> Result<Self> {
+        ensure!(p.len() >= TX_LEN, "message too short");
+        let tx_id: [u8; TX_LEN] = p[..TX_LEN].try_into().expect("length checked");
         let tx_id = stun_rs::TransactionId::from(tx_id);
         let src = send_addr_from_bytes(&p[TX_LEN..])?;
 
@@ -312,8 +323,7 @@ impl Pong {
 }
 
 impl CallMeMaybe {
-    fn from_bytes(ver: u8, p: &[u8]) -> Result<Self> {
-        ensure!(ver == V0, "invalid version");
+    fn from_bytes(p: &[u8]) -> Result<Self> {
         ensure!(p.len() % EP_LENGTH == 0, "invalid entries");
 
         let num_entries = p.len() / EP_LENGTH;
@@ -355,18 +365,18 @@ impl Message {
 
         let t = MessageType::try_from(p[0]).map_err(|v| anyhow!("unknown message type: {}", v))?;
         let ver = p[1];
-        let p = &p[2..];
+        let p = v0_payload(ver, &p[2..])?;
         match t {
             MessageType::Ping => {
-                let ping = Ping::from_bytes(ver, p)?;
+                let ping = Ping::from_bytes(p)?;
                 Ok(Message::Ping(ping))
             }
             MessageType::Pong => {
-                let pong = Pong::from_bytes(ver, p)?;
+                let pong = Pong::from_bytes(p)?;
                 Ok(Message::Pong(pong))
             }
             MessageType::CallMeMaybe => {
-                let cm = CallMeMaybe::from_bytes(ver, p)?;
+                let cm = CallMeMaybe::from_bytes(p)?;
                 Ok(Message::CallMeMaybe(cm))
             }
         }
@@ -402,6 +412,21 @@ const fn msg_header(t: MessageType, ver: u8) -> [u8; HEADER_LEN] {
     [t as u8, ver]
 }
 
+/// Returns the [`V0`] part of a message payload.
+///
+/// Payloads of later versions carry the length of their [`V0`] part, anything after it
+/// are extensions this node does not know about and are skipped.
+fn v0_payload(ver: u8, p: &[u8]) -> Result<&[u8]> {
+    if ver == V0 {
+        return Ok(p);
+    }
+    ensure!(p.len() >= V0_LEN_LEN, "message too short");
+    let (len, rest) = p.split_at(V0_LEN_LEN);
+    let len = u16::from_le_bytes([len[0], len[1]]) as usize;
+    ensure!(rest.len() >= len, "message too short");
+    Ok(&rest[..len])
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -471,6 +496,64 @@ mod tests {
         }
     }
 
+    /// Encodes a message the way a node on a later version would: the version 0 payload
+    /// prefixed by its length, followed by fields unknown to us.
+    fn encode_future(msg: &Message, ver: u8, extensions: &[u8]) -> Vec<u8> {
+        let v0 = msg.as_bytes();
+        let (header, payload) = v0.split_at(HEADER_LEN);
+        let mut out = vec![header[0], ver];
+        out.extend_from_slice(&(payload.len() as u16).to_le_bytes());
+        out.extend_from_slice(payload);
+        out.extend_from_slice(extensions);
+        out
+    }
+
+    #[test]
+    fn test_future_versions() {
+        let msgs = [
+            Message::Ping(Ping {
+                tx_id: [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12].into(),
+                node_key: SecretKey::generate().public(),
+            }),
+            Message::Pong(Pong {
+                tx_id: [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12].into(),
+                ping_observed_addr: SendAddr::Relay("https://relay.example".parse().unwrap()),
+            }),
+            Message::CallMeMaybe(CallMeMaybe {
+                my_numbers: vec!["1.2.3.4:567".parse().unwrap()],
+            }),
+        ];
+        for msg in msgs {
+            // We still send version 0, which every node understands.
+            assert_eq!(msg.as_bytes()[1], V0);
+
+            for extensions in [&[][..], &[0xff, 0x00, 0x42][..]] {
+                let bytes = encode_future(&msg, 1, extensions);
+                let back = Message::from_bytes(&bytes).expect("failed to parse");
+                assert_eq!(msg, back);
+            }
+
+            // A length pointing past the end of the message is rejected.
+            let mut bytes = encode_future(&msg, 7, &[]);
+            bytes.truncate(bytes.len() - 1);
+            assert!(Message::from_bytes(&bytes).is_err());
+        }
+    }
+
+    #[test]
+    fn test_short_messages() {
+        for ty in [
+            MessageType::Ping,
+            MessageType::Pong,
+            MessageType::CallMeMaybe,
+        ] {
+            for ver in [V0, 1] {
+                let bytes = [ty as u8, ver, 0x01];
+                assert!(Message::from_bytes(&bytes).is_err());
+            }
+        }
+    }
+
     #[test]
     fn test_extraction() {
         let sender_key = SecretKey::generate();
@@ -503,3 +586,77 @@ mod tests {
         assert_eq!(msg_back, msg);
     }
 }
+
+#[cfg(test)]
+mod proptests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    /// A valid message type and arbitrary version, followed by arbitrary bytes.
+    fn typed_bytes() -> impl Strategy<Value = Vec<u8>> {
+        (
+            prop::sample::select(vec![
+                MessageType::Ping,
+                MessageType::Pong,
+                MessageType::CallMeMaybe,
+            ]),
+            any::<u8>(),
+            prop::collection::vec(any::<u8>(), 0..128),
+        )
+            .prop_map(|(ty, ver, rest)| {
+                let mut bytes = vec![ty as u8, ver];
+                bytes.extend(rest);
+                bytes
+            })
+    }
+
+    fn send_addr() -> impl Strategy<Value = SendAddr> {
+        (any::<[u8; 16]>(), any::<u16>()).prop_map(|(ip, port)| {
+            SendAddr::Udp(SocketAddr::new(IpAddr::from(ip).to_canonical(), port))
+        })
+    }
+
+    proptest! {
+        // Disco messages are sent by remote nodes, decoding them must fail with an error,
+        // never panic.
+        #[test]
+        fn message_arbitrary_bytes(bytes in prop::collection::vec(any::<u8>(), 0..128)) {
+            let _ = Message::from_bytes(&bytes);
+        }
+
+        #[test]
+        fn message_arbitrary_payload(bytes in typed_bytes()) {
+            let _ = Message::from_bytes(&bytes);
+        }
+
+        #[test]
+        fn message_truncated(
+            tx_id in any::<[u8; 12]>(),
+            ping_observed_addr in send_addr(),
+            len in 0usize..64,
+        ) {
+            let msg = Message::Pong(Pong {
+                tx_id: tx_id.into(),
+                ping_observed_addr,
+            });
+            let bytes = msg.as_bytes();
+            let len = len.min(bytes.len());
+            let res = Message::from_bytes(&bytes[..len]);
+            if len == bytes.len() {
+                prop_assert_eq!(res.unwrap(), msg);
+            } else {
+                prop_assert!(res.is_err());
+            }
+        }
+
+        #[test]
+        fn wrapper_arbitrary_bytes(rest in prop::collection::vec(any::<u8>(), 0..128)) {
+            let mut bytes = MAGIC.as_bytes().to_vec();
+            bytes.extend(rest);
+            if let Some((_, sealed_box)) = source_and_box(&bytes) {
+                prop_assert_eq!(sealed_box.len(), bytes.len() - MESSAGE_HEADER_LEN);
+            }
+        }
+    }
+}
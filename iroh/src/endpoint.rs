@@ -13,30 +13,34 @@
 
 use std::{
     any::Any,
+    collections::BTreeSet,
     future::{Future, IntoFuture},
     net::{IpAddr, SocketAddr, SocketAddrV4, SocketAddrV6},
     pin::Pin,
-    sync::Arc,
+    sync::{Arc, RwLock},
     task::Poll,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use anyhow::{anyhow, bail, Context, Result};
 use derive_more::Debug;
 use futures_lite::{Stream, StreamExt};
 use iroh_base::relay_map::RelayMap;
+use iroh_metrics::inc;
 use pin_project::pin_project;
 use tokio_util::sync::CancellationToken;
 use tracing::{debug, instrument, trace, warn};
 use url::Url;
 
 use crate::{
+    defaults::DEFAULT_SLOW_HANDSHAKE_THRESHOLD,
     discovery::{
         dns::DnsDiscovery, pkarr::PkarrPublisher, ConcurrentDiscovery, Discovery, DiscoveryTask,
     },
     dns::{default_resolver, DnsResolver},
     key::{PublicKey, SecretKey},
     magicsock::{self, Handle, QuicMappedAddr},
+    metrics::MagicsockMetrics,
     tls, NodeId, RelayUrl,
 };
 
@@ -86,14 +90,20 @@ type DiscoveryBuilder = Box<dyn FnOnce(&SecretKey) -> Option<Box<dyn Discovery>>
 pub struct Builder {
     secret_key: Option<SecretKey>,
     relay_mode: RelayMode,
+    relay_preference: Vec<RelayUrl>,
+    relay_keepalive_interval: Option<Duration>,
+    relay_keepalive_timeout: Duration,
     alpn_protocols: Vec<Vec<u8>>,
     transport_config: Option<quinn::TransportConfig>,
     keylog: bool,
+    slow_handshake_threshold: Option<Duration>,
     #[debug(skip)]
     discovery: Vec<DiscoveryBuilder>,
     proxy_url: Option<Url>,
     /// List of known nodes. See [`Builder::known_nodes`].
     node_map: Option<Vec<NodeAddr>>,
+    /// Addresses pinned at startup. See [`Builder::pinned_addrs`].
+    pinned_addrs: Vec<(NodeId, SocketAddr)>,
     dns_resolver: Option<DnsResolver>,
     #[cfg(any(test, feature = "test-utils"))]
     #[cfg_attr(iroh_docsrs, doc(cfg(any(test, feature = "test-utils"))))]
@@ -107,12 +117,17 @@ impl Default for Builder {
         Self {
             secret_key: Default::default(),
             relay_mode: default_relay_mode(),
+            relay_preference: Vec::new(),
+            relay_keepalive_interval: Some(iroh_relay::defaults::DEFAULT_KEEPALIVE_INTERVAL),
+            relay_keepalive_timeout: iroh_relay::defaults::DEFAULT_KEEPALIVE_TIMEOUT,
             alpn_protocols: Default::default(),
             transport_config: Default::default(),
             keylog: Default::default(),
+            slow_handshake_threshold: Some(DEFAULT_SLOW_HANDSHAKE_THRESHOLD),
             discovery: Default::default(),
             proxy_url: None,
             node_map: None,
+            pinned_addrs: Vec::new(),
             dns_resolver: None,
             #[cfg(any(test, feature = "test-utils"))]
             insecure_skip_relay_cert_verify: false,
@@ -136,6 +151,7 @@ impl Builder {
             transport_config: Arc::new(self.transport_config.unwrap_or_default()),
             keylog: self.keylog,
             secret_key: secret_key.clone(),
+            slow_handshake_threshold: self.slow_handshake_threshold,
         };
         let dns_resolver = self
             .dns_resolver
@@ -155,7 +171,11 @@ impl Builder {
             addr_v6: self.addr_v6,
             secret_key,
             relay_map,
+            relay_preference: self.relay_preference,
+            relay_keepalive_interval: self.relay_keepalive_interval,
+            relay_keepalive_timeout: self.relay_keepalive_timeout,
             node_map: self.node_map,
+            pinned_addrs: self.pinned_addrs,
             discovery,
             proxy_url: self.proxy_url,
             dns_resolver,
@@ -233,6 +253,44 @@ impl Builder {
         self
     }
 
+    /// Sets an ordered list of relays to prefer as the home relay.
+    ///
+    /// By default the home relay is the relay with the lowest latency.  With a preference
+    /// list, the first relay in the list which the latest net report could reach becomes the
+    /// home relay instead.  If it stops answering probes, or connecting to it fails several
+    /// times in a row, the next net report moves on to the next relay in the list.  Such a
+    /// relay is retried after a few minutes.  Only when none of them are usable does the
+    /// endpoint fall back to picking by latency.
+    ///
+    /// The relays must be part of the relay map configured with [`Builder::relay_mode`],
+    /// others are ignored.
+    pub fn relay_preference(mut self, relays: Vec<RelayUrl>) -> Self {
+        self.relay_preference = relays;
+        self
+    }
+
+    /// Sets the interval at which keepalive pings are sent to connected relay servers.
+    ///
+    /// If a relay server does not answer within the
+    /// [keepalive timeout](Builder::relay_keepalive_timeout), the connection to it is
+    /// considered dead and is re-established.  Lowering the interval detects dropped
+    /// connections sooner, raising it saves battery and bandwidth on idle devices.
+    ///
+    /// `None` disables keepalive pings.  Defaults to
+    /// [`DEFAULT_KEEPALIVE_INTERVAL`](crate::relay::defaults::DEFAULT_KEEPALIVE_INTERVAL).
+    pub fn relay_keepalive_interval(mut self, interval: Option<Duration>) -> Self {
+        self.relay_keepalive_interval = interval;
+        self
+    }
+
+    /// Sets how long to wait for a relay server to answer a keepalive ping.
+    ///
+    /// Defaults to [`DEFAULT_KEEPALIVE_TIMEOUT`](crate::relay::defaults::DEFAULT_KEEPALIVE_TIMEOUT).
+    pub fn relay_keepalive_timeout(mut self, timeout: Duration) -> Self {
+        self.relay_keepalive_timeout = timeout;
+        self
+    }
+
     /// Removes all discovery services from the builder.
     pub fn clear_discovery(mut self) -> Self {
         self.discovery.clear();
@@ -351,6 +409,16 @@ impl Builder {
         self
     }
 
+    /// Pins direct addresses for remote nodes when the endpoint starts.
+    ///
+    /// This restores pins saved from [`RemoteInfo::pinned_addr`], which are not part of the
+    /// [`NodeAddr`]s given to [`Builder::known_nodes`].  See [`Endpoint::set_pinned_addr`]
+    /// for what pinning an address does.  A pin for this endpoint's own node id is ignored.
+    pub fn pinned_addrs(mut self, pins: impl IntoIterator<Item = (NodeId, SocketAddr)>) -> Self {
+        self.pinned_addrs = pins.into_iter().collect();
+        self
+    }
+
     // # Methods for more specialist customisation.
 
     /// Sets a custom [`quinn::TransportConfig`] for this endpoint.
@@ -408,6 +476,16 @@ impl Builder {
         self
     }
 
+    /// Sets the duration after which a QUIC handshake is logged as slow.
+    ///
+    /// Handshakes taking longer than this, for both outgoing and incoming connections,
+    /// are logged with a warning which includes the remote node id when it is known.
+    /// `None` disables the warning.  Defaults to [`DEFAULT_SLOW_HANDSHAKE_THRESHOLD`].
+    pub fn slow_handshake_threshold(mut self, threshold: Option<Duration>) -> Self {
+        self.slow_handshake_threshold = threshold;
+        self
+    }
+
     /// Skip verification of SSL certificates from relay servers
     ///
     /// May only be used in tests.
@@ -425,6 +503,7 @@ struct StaticConfig {
     secret_key: SecretKey,
     transport_config: Arc<quinn::TransportConfig>,
     keylog: bool,
+    slow_handshake_threshold: Option<Duration>,
 }
 
 impl StaticConfig {
@@ -486,6 +565,8 @@ pub struct Endpoint {
     rtt_actor: Arc<rtt_actor::RttHandle>,
     cancel_token: CancellationToken,
     static_config: Arc<StaticConfig>,
+    /// Nodes we refuse to connect with, see [`Endpoint::block_node`].
+    blocked_nodes: Arc<RwLock<BTreeSet<NodeId>>>,
 }
 
 impl Endpoint {
@@ -537,6 +618,7 @@ impl Endpoint {
             rtt_actor: Arc::new(rtt_actor::RttHandle::new()),
             cancel_token: CancellationToken::new(),
             static_config: Arc::new(static_config),
+            blocked_nodes: Default::default(),
         })
     }
 
@@ -585,6 +667,9 @@ impl Endpoint {
                 node_addr.node_id.fmt_short()
             );
         }
+        if self.is_blocked(&node_addr.node_id) {
+            bail!("Node {} is blocked", node_addr.node_id.fmt_short());
+        }
 
         if !node_addr.info.is_empty() {
             self.add_node_addr(node_addr.clone())?;
@@ -653,9 +738,16 @@ impl Endpoint {
             .endpoint
             .connect_with(client_config, addr.0, "localhost")?;
 
-        let connection = connect
-            .await
-            .context("failed connecting to remote endpoint")?;
+        let started = Instant::now();
+        let res = connect.await;
+        self.note_handshake_duration(started, Some(node_id), "outgoing", res.is_ok());
+        let connection = match res {
+            Ok(connection) => connection,
+            Err(err) => {
+                inc!(MagicsockMetrics, connection_handshake_failure);
+                return Err(err).context("failed connecting to remote endpoint");
+            }
+        };
 
         let rtt_msg = RttMessage::NewConnection {
             connection: connection.weak_handle(),
@@ -670,6 +762,36 @@ impl Endpoint {
         Ok(connection)
     }
 
+    /// Warns if a handshake took longer than [`Builder::slow_handshake_threshold`].
+    ///
+    /// The node id of an incoming connection is only known once its handshake succeeded.
+    fn note_handshake_duration(
+        &self,
+        started: Instant,
+        remote_node: Option<NodeId>,
+        direction: &'static str,
+        success: bool,
+    ) {
+        let Some(threshold) = self.static_config.slow_handshake_threshold else {
+            return;
+        };
+        let elapsed = started.elapsed();
+        if elapsed <= threshold {
+            return;
+        }
+        match remote_node {
+            Some(node_id) => warn!(
+                remote_node = %node_id.fmt_short(),
+                direction,
+                success,
+                ?elapsed,
+                ?threshold,
+                "slow handshake"
+            ),
+            None => warn!(direction, success, ?elapsed, ?threshold, "slow handshake"),
+        }
+    }
+
     /// Accepts an incoming connection on the endpoint.
     ///
     /// Only connections with the ALPNs configured in [`Builder::alpns`] will be accepted.
@@ -741,8 +863,81 @@ impl Endpoint {
         self.msock.add_node_addr(node_addr, source)
     }
 
+    /// Pins a direct address to use for all traffic to a remote node.
+    ///
+    /// While an address is pinned the automatic path selection is bypassed for this node:
+    /// all packets are sent to the pinned address and the relay server is never used, not
+    /// even as a fallback.  This is meant for stable links where the address is known to
+    /// work, e.g. between nodes in the same datacenter.  Pass `None` to remove the pin and
+    /// return to automatic path selection.
+    ///
+    /// Nodes with a pinned address are never pruned from the address book.  Pins are not
+    /// part of [`NodeAddr`], so they are not restored by [`Builder::known_nodes`].  They
+    /// are reported in [`RemoteInfo::pinned_addr`] instead, the application can save them
+    /// from there and restore them with [`Builder::pinned_addrs`].
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if we attempt to pin an address for our own [`NodeId`].
+    pub fn set_pinned_addr(&self, node_id: NodeId, addr: Option<SocketAddr>) -> Result<()> {
+        if node_id == self.node_id() {
+            bail!(
+                "Pinning an address for our own node is not supported ({} is the node id of this node)",
+                node_id.fmt_short()
+            );
+        }
+        self.msock.set_pinned_addr(node_id, addr);
+        Ok(())
+    }
+
+    /// Blocks all new connections with a remote node.
+    ///
+    /// [`Endpoint::connect`] to a blocked node fails, and incoming connections from it are
+    /// closed as soon as the handshake has authenticated the remote node.  Connections
+    /// which already exist are not affected, close them yourself if needed.
+    ///
+    /// Connections accepted early through [`Connecting::into_0rtt`] are not checked, as the
+    /// remote node is not authenticated at that point.
+    ///
+    /// The block list only lives as long as the endpoint, applications which want to keep
+    /// it across restarts should save [`Endpoint::blocked_nodes`] and restore it.
+    pub fn block_node(&self, node_id: NodeId) {
+        self.blocked_nodes
+            .write()
+            .expect("not poisoned")
+            .insert(node_id);
+    }
+
+    /// Removes a node from the block list.
+    ///
+    /// Returns `true` if the node was blocked.
+    pub fn unblock_node(&self, node_id: &NodeId) -> bool {
+        self.blocked_nodes
+            .write()
+            .expect("not poisoned")
+            .remove(node_id)
+    }
+
     // # Getter methods for properties of this Endpoint itself.
 
+    /// Returns `true` if the node is blocked, see [`Endpoint::block_node`].
+    pub fn is_blocked(&self, node_id: &NodeId) -> bool {
+        self.blocked_nodes
+            .read()
+            .expect("not poisoned")
+            .contains(node_id)
+    }
+
+    /// Returns all blocked nodes, see [`Endpoint::block_node`].
+    pub fn blocked_nodes(&self) -> Vec<NodeId> {
+        self.blocked_nodes
+            .read()
+            .expect("not poisoned")
+            .iter()
+            .copied()
+            .collect()
+    }
+
     /// Returns the secret_key of this endpoint.
     pub fn secret_key(&self) -> &SecretKey {
         &self.static_config.secret_key
@@ -1103,6 +1298,7 @@ impl Incoming {
         self.inner.accept().map(|conn| Connecting {
             inner: conn,
             ep: self.ep,
+            started: Instant::now(),
         })
     }
 
@@ -1120,6 +1316,7 @@ impl Incoming {
             .map(|conn| Connecting {
                 inner: conn,
                 ep: self.ep,
+                started: Instant::now(),
             })
     }
 
@@ -1170,6 +1367,7 @@ impl IntoFuture for Incoming {
         IncomingFuture {
             inner: self.inner.into_future(),
             ep: self.ep,
+            started: Instant::now(),
         }
     }
 }
@@ -1181,6 +1379,8 @@ pub struct IncomingFuture {
     #[pin]
     inner: quinn::IncomingFuture,
     ep: Endpoint,
+    /// When the handshake started, see [`Builder::slow_handshake_threshold`].
+    started: Instant,
 }
 
 impl Future for IncomingFuture {
@@ -1190,8 +1390,19 @@ impl Future for IncomingFuture {
         let this = self.project();
         match this.inner.poll(cx) {
             Poll::Pending => Poll::Pending,
-            Poll::Ready(Err(err)) => Poll::Ready(Err(err)),
+            Poll::Ready(Err(err)) => {
+                inc!(MagicsockMetrics, connection_handshake_failure);
+                this.ep
+                    .note_handshake_duration(*this.started, None, "incoming", false);
+                Poll::Ready(Err(err))
+            }
             Poll::Ready(Ok(conn)) => {
+                let remote_node = get_remote_node_id(&conn).ok();
+                this.ep
+                    .note_handshake_duration(*this.started, remote_node, "incoming", true);
+                if close_if_blocked(&conn, this.ep) {
+                    return Poll::Ready(Err(ConnectionError::LocallyClosed));
+                }
                 try_send_rtt_msg(&conn, this.ep);
                 Poll::Ready(Ok(conn))
             }
@@ -1206,6 +1417,8 @@ pub struct Connecting {
     #[pin]
     inner: quinn::Connecting,
     ep: Endpoint,
+    /// When the handshake started, see [`Builder::slow_handshake_threshold`].
+    started: Instant,
 }
 
 impl Connecting {
@@ -1216,7 +1429,11 @@ impl Connecting {
                 try_send_rtt_msg(&conn, &self.ep);
                 Ok((conn, zrtt_accepted))
             }
-            Err(inner) => Err(Self { inner, ep: self.ep }),
+            Err(inner) => Err(Self {
+                inner,
+                ep: self.ep,
+                started: self.started,
+            }),
         }
     }
 
@@ -1257,8 +1474,19 @@ impl Future for Connecting {
         let this = self.project();
         match this.inner.poll(cx) {
             Poll::Pending => Poll::Pending,
-            Poll::Ready(Err(err)) => Poll::Ready(Err(err)),
+            Poll::Ready(Err(err)) => {
+                inc!(MagicsockMetrics, connection_handshake_failure);
+                this.ep
+                    .note_handshake_duration(*this.started, None, "incoming", false);
+                Poll::Ready(Err(err))
+            }
             Poll::Ready(Ok(conn)) => {
+                let remote_node = get_remote_node_id(&conn).ok();
+                this.ep
+                    .note_handshake_duration(*this.started, remote_node, "incoming", true);
+                if close_if_blocked(&conn, this.ep) {
+                    return Poll::Ready(Err(ConnectionError::LocallyClosed));
+                }
                 try_send_rtt_msg(&conn, this.ep);
                 Poll::Ready(Ok(conn))
             }
@@ -1266,6 +1494,21 @@ impl Future for Connecting {
     }
 }
 
+/// Closes an incoming connection if the remote node is blocked.
+///
+/// Returns `true` if the connection was closed.
+fn close_if_blocked(conn: &Connection, ep: &Endpoint) -> bool {
+    let Ok(node_id) = get_remote_node_id(conn) else {
+        return false;
+    };
+    if !ep.is_blocked(&node_id) {
+        return false;
+    }
+    debug!(remote = %node_id.fmt_short(), "closing connection from blocked node");
+    conn.close(0u32.into(), b"blocked");
+    true
+}
+
 /// Extract the [`PublicKey`] from the peer's TLS certificate.
 // TODO: make this a method now
 pub fn get_remote_node_id(connection: &Connection) -> Result<PublicKey> {
@@ -1605,6 +1848,31 @@ mod tests {
         assert_eq!(conn_addr, direct_addr);
     }
 
+    /// Test that pins given to the builder are applied when the endpoint starts
+    #[tokio::test]
+    async fn endpoint_pinned_addrs() {
+        let _guard = iroh_test::logging::setup();
+
+        let secret_key = SecretKey::generate();
+        let peer_id = SecretKey::generate().public();
+        let pinned: SocketAddr = (std::net::Ipv4Addr::LOCALHOST, 8759u16).into();
+
+        let endpoint = Endpoint::builder()
+            .secret_key(secret_key.clone())
+            .pinned_addrs([(peer_id, pinned), (secret_key.public(), pinned)])
+            .bind()
+            .await
+            .unwrap();
+
+        let info = endpoint.remote_info(peer_id).unwrap();
+        assert_eq!(info.pinned_addr, Some(pinned));
+        // pinning our own node id is ignored
+        assert!(endpoint.remote_info(secret_key.public()).is_none());
+        assert_eq!(endpoint.remote_info_iter().count(), 1);
+
+        endpoint.close().await.unwrap();
+    }
+
     #[tokio::test]
     async fn endpoint_relay_connect_loop() {
         let _logging_guard = iroh_test::logging::setup();
@@ -1712,6 +1980,85 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn endpoint_relay_preference() -> Result<()> {
+        let _logging_guard = iroh_test::logging::setup();
+        let (relay_map_a, _relay_url_a, _relay_guard_a) = run_relay_server().await?;
+        let (relay_map_b, relay_url_b, _relay_guard_b) = run_relay_server().await?;
+        let relay_map =
+            RelayMap::from_nodes(relay_map_a.nodes().chain(relay_map_b.nodes()).cloned())?;
+        let unknown_url: RelayUrl = "https://unknown.example.com".parse()?;
+
+        let ep = Endpoint::builder()
+            .insecure_skip_relay_cert_verify(true)
+            .relay_mode(RelayMode::Custom(relay_map))
+            .relay_preference(vec![unknown_url, relay_url_b.clone()])
+            .bind()
+            .await?;
+
+        // The unknown relay is skipped whether or not the net report reached the relays.
+        let home_relay =
+            tokio::time::timeout(Duration::from_secs(10), ep.watch_home_relay().next())
+                .await?
+                .expect("home relay stream ended");
+        assert_eq!(home_relay, relay_url_b);
+        assert_eq!(ep.home_relay(), Some(relay_url_b));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn endpoint_block_node() -> Result<()> {
+        let _logging_guard = iroh_test::logging::setup();
+        let ep1 = Endpoint::builder()
+            .alpns(vec![TEST_ALPN.to_vec()])
+            .relay_mode(RelayMode::Disabled)
+            .bind()
+            .await?;
+        let ep2 = Endpoint::builder()
+            .alpns(vec![TEST_ALPN.to_vec()])
+            .relay_mode(RelayMode::Disabled)
+            .bind()
+            .await?;
+        let ep1_nodeaddr = ep1.node_addr().await?;
+        let ep2_nodeaddr = ep2.node_addr().await?;
+
+        ep1.block_node(ep2.node_id());
+        assert!(ep1.is_blocked(&ep2.node_id()));
+        assert_eq!(ep1.blocked_nodes(), vec![ep2.node_id()]);
+
+        // Dialing a blocked node fails right away.
+        assert!(ep1.connect(ep2_nodeaddr, TEST_ALPN).await.is_err());
+
+        // Connections from a blocked node are closed once the handshake is done.
+        let accept_task = tokio::spawn({
+            let ep1 = ep1.clone();
+            async move {
+                let incoming = ep1.accept().await.expect("endpoint closed");
+                incoming.accept()?.await
+            }
+        });
+        if let Ok(conn) = ep2.connect(ep1_nodeaddr, TEST_ALPN).await {
+            let err = conn.closed().await;
+            assert!(
+                matches!(&err, ConnectionError::ApplicationClosed(close) if &close.reason[..] == b"blocked"),
+                "unexpected close: {err:?}"
+            );
+        }
+        assert!(matches!(
+            accept_task.await?,
+            Err(ConnectionError::LocallyClosed)
+        ));
+
+        assert!(ep1.unblock_node(&ep2.node_id()));
+        assert!(!ep1.unblock_node(&ep2.node_id()));
+        assert!(ep1.blocked_nodes().is_empty());
+
+        ep1.close().await?;
+        ep2.close().await?;
+        Ok(())
+    }
+
     #[tokio::test]
     async fn endpoint_bidi_send_recv() {
         let _logging_guard = iroh_test::logging::setup();
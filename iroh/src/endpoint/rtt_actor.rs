@@ -149,10 +149,16 @@ impl RttActor {
                             new_type = ?new_conn_type,
                             "Congestion controller state reset",
                         );
-                        if !*was_direct_before && matches!(new_conn_type, ConnectionType::Direct(_))
-                        {
-                            *was_direct_before = true;
-                            inc!(MagicsockMetrics, connection_became_direct);
+                        if let ConnectionType::Direct(addr) = new_conn_type {
+                            if !*was_direct_before {
+                                *was_direct_before = true;
+                                inc!(MagicsockMetrics, connection_became_direct);
+                                if addr.is_ipv4() {
+                                    inc!(MagicsockMetrics, connection_became_direct_ipv4);
+                                } else {
+                                    inc!(MagicsockMetrics, connection_became_direct_ipv6);
+                                }
+                            }
                         }
                     } else {
                         debug!(
@@ -171,19 +177,42 @@ impl RttActor {
     }
 
     /// Performs cleanup for closed connection.
+    ///
+    /// This also records whether the closed connection ever became direct, so the
+    /// hole punching success rate can be derived from the metrics.
     fn do_connections_cleanup(&mut self) {
-        for (key, (handle, node_id, _)) in self.connections.iter() {
-            if !handle.is_alive() {
-                trace!(node_id = %node_id.fmt_short(), "removing stale connection");
-                self.connection_events.remove(*key);
+        for was_direct in self.remove_closed_connections() {
+            if was_direct {
+                inc!(MagicsockMetrics, connection_closed_direct);
+            } else {
+                inc!(MagicsockMetrics, connection_closed_relay_only);
             }
         }
     }
+
+    /// Removes closed connections, returning for each whether it ever became direct.
+    fn remove_closed_connections(&mut self) -> Vec<bool> {
+        let mut closed = Vec::new();
+        self.connections
+            .retain(|key, (handle, node_id, was_direct_before)| {
+                if handle.is_alive() {
+                    return true;
+                }
+                trace!(node_id = %node_id.fmt_short(), "removing stale connection");
+                self.connection_events.remove(*key);
+                closed.push(*was_direct_before);
+                false
+            });
+        closed
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{endpoint::Endpoint, RelayMode};
+
+    const TEST_ALPN: &[u8] = b"n0/iroh/test";
 
     #[tokio::test]
     async fn test_actor_mspc_close() {
@@ -205,4 +234,72 @@ mod tests {
             .expect("timeout - actor did not finish");
         assert!(task_res.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_closed_connection_outcome() -> anyhow::Result<()> {
+        let _guard = iroh_test::logging::setup();
+
+        let ep1 = Endpoint::builder()
+            .alpns(vec![TEST_ALPN.to_vec()])
+            .relay_mode(RelayMode::Disabled)
+            .bind()
+            .await?;
+        let ep2 = Endpoint::builder()
+            .relay_mode(RelayMode::Disabled)
+            .bind()
+            .await?;
+        let accept = tokio::spawn({
+            let ep1 = ep1.clone();
+            async move {
+                let conn = ep1.accept().await.expect("endpoint closed").await?;
+                conn.closed().await;
+                anyhow::Ok(())
+            }
+        });
+        let conn = ep2.connect(ep1.node_addr().await?, TEST_ALPN).await?;
+
+        let mut actor = RttActor {
+            connection_events: stream_group::StreamGroup::new().keyed(),
+            connections: HashMap::new(),
+            tick: Notify::new(),
+        };
+        actor.handle_new_connection(
+            conn.weak_handle(),
+            ep2.conn_type_stream(ep1.node_id())?,
+            ep1.node_id(),
+        );
+        assert!(actor.remove_closed_connections().is_empty());
+
+        // Mark the connection as having become direct.
+        let key = *actor.connections.keys().next().expect("connection added");
+        let addr = conn.remote_address();
+        actor.do_reset_rtt(Some((key, ConnectionType::Direct(addr))));
+        assert!(actor.connections[&key].2);
+
+        conn.close(0u32.into(), b"done");
+        drop(conn);
+        accept.await??;
+
+        // The connection is gone once quinn finished draining it.
+        let closed = tokio::time::timeout(Duration::from_secs(10), async {
+            loop {
+                let closed = actor.remove_closed_connections();
+                if !closed.is_empty() {
+                    return closed;
+                }
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+        })
+        .await?;
+        assert_eq!(closed, vec![true]);
+        assert!(actor.connections.is_empty());
+        assert!(actor.connection_events.is_empty());
+
+        // Each connection is only counted once.
+        assert!(actor.remove_closed_connections().is_empty());
+
+        ep1.close().await?;
+        ep2.close().await?;
+        Ok(())
+    }
 }
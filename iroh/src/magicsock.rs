@@ -90,6 +90,12 @@ const ENDPOINTS_FRESH_ENOUGH_DURATION: Duration = Duration::from_secs(27);
 
 const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
 
+/// Number of failed connection attempts in a row after which a preferred relay is skipped.
+const RELAY_MAX_CONNECT_FAILURES: usize = 3;
+
+/// How long a preferred relay is skipped after [`RELAY_MAX_CONNECT_FAILURES`].
+const RELAY_CONNECT_FAILURE_TIMEOUT: Duration = Duration::from_secs(60 * 5);
+
 /// Contains options for `MagicSock::listen`.
 #[derive(derive_more::Debug)]
 pub(crate) struct Options {
@@ -108,9 +114,21 @@ pub(crate) struct Options {
     /// The [`RelayMap`] to use, leave empty to not use a relay server.
     pub(crate) relay_map: RelayMap,
 
+    /// Relays to prefer as home relay, in order, over the lowest latency one.
+    pub(crate) relay_preference: Vec<RelayUrl>,
+
+    /// Interval of keepalive pings to relay servers, `None` disables them.
+    pub(crate) relay_keepalive_interval: Option<Duration>,
+
+    /// How long to wait for the pong to a relay keepalive ping before reconnecting.
+    pub(crate) relay_keepalive_timeout: Duration,
+
     /// An optional [`NodeMap`], to restore information about nodes.
     pub(crate) node_map: Option<Vec<NodeAddr>>,
 
+    /// Direct addresses to pin for nodes, applied after loading the [`NodeMap`].
+    pub(crate) pinned_addrs: Vec<(NodeId, SocketAddr)>,
+
     /// Optional node discovery mechanism.
     pub(crate) discovery: Option<Box<dyn Discovery>>,
 
@@ -138,7 +156,11 @@ impl Default for Options {
             addr_v6: None,
             secret_key: SecretKey::generate(),
             relay_map: RelayMap::empty(),
+            relay_preference: Vec::new(),
+            relay_keepalive_interval: Some(iroh_relay::defaults::DEFAULT_KEEPALIVE_INTERVAL),
+            relay_keepalive_timeout: iroh_relay::defaults::DEFAULT_KEEPALIVE_TIMEOUT,
             node_map: None,
+            pinned_addrs: Vec::new(),
             discovery: None,
             proxy_url: None,
             dns_resolver: crate::dns::default_resolver().clone(),
@@ -177,10 +199,18 @@ pub(crate) struct Handle {
 pub(crate) struct MagicSock {
     actor_sender: mpsc::Sender<ActorMessage>,
     relay_actor_sender: mpsc::Sender<RelayActorMessage>,
+    /// Channel for disco messages to the [`RelayActor`].
+    ///
+    /// These are handled before anything queued on `relay_actor_sender`.
+    relay_disco_sender: mpsc::Sender<RelayActorMessage>,
     /// String representation of the node_id of this node.
     me: String,
     /// Proxy
     proxy_url: Option<Url>,
+    /// Interval of keepalive pings to relay servers, `None` disables them.
+    relay_keepalive_interval: Option<Duration>,
+    /// How long to wait for the pong to a relay keepalive ping before reconnecting.
+    relay_keepalive_timeout: Duration,
     /// Queue to receive datagrams from relays for [`AsyncUdpSocket::poll_recv`].
     ///
     /// Relay datagrams received by relays are put into this queue and consumed by
@@ -242,6 +272,9 @@ pub(crate) struct MagicSock {
     /// completes
     pending_call_me_maybes: parking_lot::Mutex<HashMap<PublicKey, RelayUrl>>,
 
+    /// Failed connection attempts in a row to each relay, and when the last one failed.
+    relay_connect_failures: parking_lot::Mutex<HashMap<RelayUrl, (usize, Instant)>>,
+
     /// Indicates the direct addr update state.
     direct_addr_update_state: DirectAddrUpdateState,
 
@@ -271,6 +304,34 @@ impl MagicSock {
         self.proxy_url.as_ref()
     }
 
+    /// Returns the keepalive interval and timeout for relay connections.
+    pub(crate) fn relay_keepalive(&self) -> (Option<Duration>, Duration) {
+        (self.relay_keepalive_interval, self.relay_keepalive_timeout)
+    }
+
+    /// Records whether connecting to the relay at `url` succeeded.
+    fn note_relay_connect(&self, url: &RelayUrl, success: bool) {
+        let mut failures = self.relay_connect_failures.lock();
+        if success {
+            failures.remove(url);
+        } else {
+            let (count, last) = failures.entry(url.clone()).or_insert((0, Instant::now()));
+            *count += 1;
+            *last = Instant::now();
+        }
+    }
+
+    /// Returns `true` if connecting to the relay at `url` recently failed repeatedly.
+    fn relay_is_failing(&self, url: &RelayUrl) -> bool {
+        self.relay_connect_failures
+            .lock()
+            .get(url)
+            .is_some_and(|(count, last)| {
+                *count >= RELAY_MAX_CONNECT_FAILURES
+                    && last.elapsed() < RELAY_CONNECT_FAILURE_TIMEOUT
+            })
+    }
+
     /// Sets the relay node with the best latency.
     ///
     /// If we are not connected to any relay nodes, set this to `None`.
@@ -388,6 +449,11 @@ impl MagicSock {
         }
     }
 
+    /// Pins a direct address for a node, or removes the pin with `None`.
+    pub(crate) fn set_pinned_addr(&self, node_id: NodeId, addr: Option<SocketAddr>) {
+        self.node_map.set_pinned_addr(node_id, addr)
+    }
+
     /// Stores a new set of direct addresses.
     ///
     /// If the direct addresses have changed from the previous set, they are published to
@@ -589,6 +655,26 @@ impl MagicSock {
         url: &RelayUrl,
         node: NodeId,
         contents: RelayContents,
+    ) -> io::Result<()> {
+        self.try_send_relay_inner(url, node, contents, false)
+    }
+
+    /// Queues a disco packet for the relay, ahead of any payload from [`Self::try_send_relay`].
+    fn try_send_relay_disco(
+        &self,
+        url: &RelayUrl,
+        node: NodeId,
+        contents: RelayContents,
+    ) -> io::Result<()> {
+        self.try_send_relay_inner(url, node, contents, true)
+    }
+
+    fn try_send_relay_inner(
+        &self,
+        url: &RelayUrl,
+        node: NodeId,
+        contents: RelayContents,
+        disco: bool,
     ) -> io::Result<()> {
         trace!(
             node = %node.fmt_short(),
@@ -597,12 +683,21 @@ impl MagicSock {
             len = contents.iter().map(|c| c.len()).sum::<usize>(),
             "send relay",
         );
-        let msg = RelayActorMessage::Send {
-            url: url.clone(),
-            contents,
-            remote_node: node,
+        let res = if disco {
+            self.relay_disco_sender
+                .try_send(RelayActorMessage::SendDisco {
+                    url: url.clone(),
+                    contents,
+                    remote_node: node,
+                })
+        } else {
+            self.relay_actor_sender.try_send(RelayActorMessage::Send {
+                url: url.clone(),
+                contents,
+                remote_node: node,
+            })
         };
-        match self.relay_actor_sender.try_send(msg) {
+        match res {
             Ok(_) => {
                 trace!(node = %node.fmt_short(), relay_url = %url,
                        "send relay: message queued");
@@ -1212,7 +1307,7 @@ impl MagicSock {
         debug!(node = %dst.fmt_short(), %url, %msg, "send disco message (relay)");
         let pkt = self.encode_disco_message(dst, &msg);
         inc!(MagicsockMetrics, send_disco_relay);
-        match self.try_send_relay(url, dst, smallvec![pkt]) {
+        match self.try_send_relay_disco(url, dst, smallvec![pkt]) {
             Ok(()) => {
                 if let disco::Message::CallMeMaybe(CallMeMaybe { ref my_numbers }) = msg {
                     event!(
@@ -1506,7 +1601,11 @@ impl Handle {
             addr_v6,
             secret_key,
             relay_map,
+            relay_preference,
+            relay_keepalive_interval,
+            relay_keepalive_timeout,
             node_map,
+            pinned_addrs,
             discovery,
             dns_resolver,
             proxy_url,
@@ -1516,6 +1615,18 @@ impl Handle {
 
         let relay_datagrams_queue = Arc::new(RelayDatagramsQueue::new());
 
+        // Preferred relays can only become the home relay if we know how to reach them.
+        let relay_preference = relay_preference
+            .into_iter()
+            .filter(|url| {
+                let known = relay_map.contains_node(url);
+                if !known {
+                    warn!(%url, "ignoring preferred relay which is not in the relay map");
+                }
+                known
+            })
+            .collect();
+
         let (pconn4, pconn6) = bind(addr_v4, addr_v6)?;
         let port = pconn4.port();
 
@@ -1537,17 +1648,27 @@ impl Handle {
 
         let (actor_sender, actor_receiver) = mpsc::channel(256);
         let (relay_actor_sender, relay_actor_receiver) = mpsc::channel(256);
+        let (relay_disco_sender, relay_disco_receiver) = mpsc::channel(256);
         let (udp_disco_sender, mut udp_disco_receiver) = mpsc::channel(256);
 
         // load the node data
         let node_map = node_map.unwrap_or_default();
         let node_map = NodeMap::load_from_vec(node_map);
+        for (node_id, addr) in pinned_addrs {
+            if node_id == secret_key.public() {
+                warn!(%addr, "ignoring pinned address for our own node");
+                continue;
+            }
+            node_map.set_pinned_addr(node_id, Some(addr));
+        }
 
         let inner = Arc::new(MagicSock {
             me,
             port: AtomicU16::new(port),
             secret_key,
             proxy_url,
+            relay_keepalive_interval,
+            relay_keepalive_timeout,
             local_addrs: std::sync::RwLock::new((ipv4_addr, ipv6_addr)),
             closing: AtomicBool::new(false),
             closed: AtomicBool::new(false),
@@ -1564,10 +1685,12 @@ impl Handle {
             disco_secrets: DiscoSecrets::default(),
             node_map,
             relay_actor_sender: relay_actor_sender.clone(),
+            relay_disco_sender,
             udp_disco_sender,
             discovery,
             direct_addrs: Default::default(),
             pending_call_me_maybes: Default::default(),
+            relay_connect_failures: Default::default(),
             direct_addr_update_state: DirectAddrUpdateState::new(),
             dns_resolver,
             #[cfg(any(test, feature = "test-utils"))]
@@ -1580,7 +1703,9 @@ impl Handle {
         let relay_actor_cancel_token = relay_actor.cancel_token();
         actor_tasks.spawn(
             async move {
-                relay_actor.run(relay_actor_receiver).await;
+                relay_actor
+                    .run(relay_actor_receiver, relay_disco_receiver)
+                    .await;
             }
             .instrument(info_span!("relay-actor")),
         );
@@ -1612,6 +1737,7 @@ impl Handle {
                     no_v4_send: false,
                     net_reporter,
                     network_monitor,
+                    relay_preference,
                 };
 
                 if let Err(err) = actor.run().await {
@@ -1928,6 +2054,11 @@ struct Actor {
     net_reporter: net_report::Client,
 
     network_monitor: netmon::Monitor,
+
+    /// Relays to prefer as home relay, in order, see [`pick_preferred_relay`].
+    ///
+    /// Only contains relays which are part of the relay map.
+    relay_preference: Vec<RelayUrl>,
 }
 
 impl Actor {
@@ -2403,6 +2534,18 @@ impl Actor {
                     .insert(format!("{rid}-v6"), d.as_secs_f64());
             }
 
+            // Skipping relays we keep failing to connect to lets the next preferred relay
+            // take over.
+            let reachable = r
+                .relay_latency
+                .iter()
+                .map(|(url, _)| url)
+                .filter(|url| !self.msock.relay_is_failing(url))
+                .collect::<Vec<_>>();
+            if let Some(url) = pick_preferred_relay(&self.relay_preference, &reachable) {
+                ni.preferred_relay = Some(url);
+            }
+
             if ni.preferred_relay.is_none() {
                 // Perhaps UDP is blocked. Pick a deterministic but arbitrary one.
                 ni.preferred_relay = self.pick_relay_fallback();
@@ -2462,6 +2605,14 @@ impl Actor {
         if my_relay.is_some() {
             return my_relay;
         }
+        // The preference list only contains relays from the relay map.
+        if let Some(url) = self
+            .relay_preference
+            .iter()
+            .find(|url| !self.msock.relay_is_failing(url))
+        {
+            return Some(url.clone());
+        }
 
         let ids = self.msock.relay_map.urls().collect::<Vec<_>>();
         let mut rng = rand::rngs::StdRng::seed_from_u64(0);
@@ -2654,6 +2805,17 @@ impl Stream for DirectAddrsStream {
     }
 }
 
+/// Returns the first relay in `preference` which is `reachable`.
+///
+/// Returns `None` if none of the preferred relays are reachable, in which case the home
+/// relay should be picked by latency.
+fn pick_preferred_relay(preference: &[RelayUrl], reachable: &[&RelayUrl]) -> Option<RelayUrl> {
+    preference
+        .iter()
+        .find(|url| reachable.contains(url))
+        .cloned()
+}
+
 /// Split a transmit containing a GSO payload into individual packets.
 ///
 /// This allocates the data.
@@ -3670,6 +3832,43 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_pick_preferred_relay() {
+        let a: RelayUrl = "https://a.example".parse().unwrap();
+        let b: RelayUrl = "https://b.example".parse().unwrap();
+        let c: RelayUrl = "https://c.example".parse().unwrap();
+        let preference = vec![a.clone(), b.clone()];
+
+        assert_eq!(
+            pick_preferred_relay(&preference, &[&c, &b, &a]),
+            Some(a.clone())
+        );
+        // The primary is unreachable, walk on to the secondary.
+        assert_eq!(pick_preferred_relay(&preference, &[&c, &b]), Some(b));
+        // None of the preferred relays are reachable, pick by latency.
+        assert_eq!(pick_preferred_relay(&preference, &[&c]), None);
+        assert_eq!(pick_preferred_relay(&[], &[&a]), None);
+    }
+
+    #[tokio::test]
+    async fn test_relay_connect_failures() {
+        let msock = MagicSock::spawn(Options::default()).await.unwrap();
+        let url: RelayUrl = "https://a.example".parse().unwrap();
+
+        for _ in 1..RELAY_MAX_CONNECT_FAILURES {
+            msock.note_relay_connect(&url, false);
+        }
+        assert!(!msock.relay_is_failing(&url));
+        msock.note_relay_connect(&url, false);
+        assert!(msock.relay_is_failing(&url));
+
+        // A successful connection resets the count.
+        msock.note_relay_connect(&url, true);
+        assert!(!msock.relay_is_failing(&url));
+
+        msock.close().await.unwrap();
+    }
+
     #[test]
     fn test_split_packets() {
         fn mk_transmit(contents: &[u8], segment_size: Option<usize>) -> quinn_udp::Transmit<'_> {
@@ -3769,7 +3968,11 @@ mod tests {
             addr_v6: None,
             secret_key: secret_key.clone(),
             relay_map: RelayMap::empty(),
+            relay_preference: Vec::new(),
+            relay_keepalive_interval: Some(iroh_relay::defaults::DEFAULT_KEEPALIVE_INTERVAL),
+            relay_keepalive_timeout: iroh_relay::defaults::DEFAULT_KEEPALIVE_TIMEOUT,
             node_map: None,
+            pinned_addrs: Vec::new(),
             discovery: None,
             dns_resolver: crate::dns::default_resolver().clone(),
             proxy_url: None,
@@ -77,8 +77,18 @@ pub struct Metrics {
 
     /// Number of connections with a successful handshake.
     pub connection_handshake_success: Counter,
+    /// Number of connections whose handshake failed.
+    pub connection_handshake_failure: Counter,
     /// Number of connections with a successful handshake that became direct.
     pub connection_became_direct: Counter,
+    /// Number of connections that first became direct over IPv4.
+    pub connection_became_direct_ipv4: Counter,
+    /// Number of connections that first became direct over IPv6.
+    pub connection_became_direct_ipv6: Counter,
+    /// Number of closed connections which were direct at some point.
+    pub connection_closed_direct: Counter,
+    /// Number of closed connections which only ever used the relay.
+    pub connection_closed_relay_only: Counter,
 }
 
 impl Default for Metrics {
@@ -148,7 +158,12 @@ impl Default for Metrics {
             nodes_contacted_directly: Counter::new("nodes_contacted_directly"),
 
             connection_handshake_success: Counter::new("connection_handshake_success"),
+            connection_handshake_failure: Counter::new("connection_handshake_failure"),
             connection_became_direct: Counter::new("connection_became_direct"),
+            connection_became_direct_ipv4: Counter::new("connection_became_direct_ipv4"),
+            connection_became_direct_ipv6: Counter::new("connection_became_direct_ipv6"),
+            connection_closed_direct: Counter::new("connection_closed_direct"),
+            connection_closed_relay_only: Counter::new("connection_closed_relay_only"),
         }
     }
 }
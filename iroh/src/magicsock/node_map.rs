@@ -144,6 +144,11 @@ impl NodeMap {
         self.inner.lock().add_node_addr(node_addr, source)
     }
 
+    /// Pins a direct address for a node, or removes the pin with `None`.
+    pub(super) fn set_pinned_addr(&self, node_id: NodeId, addr: Option<SocketAddr>) {
+        self.inner.lock().set_pinned_addr(node_id, addr)
+    }
+
     /// Number of nodes currently listed.
     pub(super) fn node_count(&self) -> usize {
         self.inner.lock().node_count()
@@ -317,6 +322,28 @@ impl NodeMapInner {
         }
     }
 
+    /// Pins a direct address for a node, inserting the node if needed.
+    ///
+    /// Removing the pin of an unknown node does nothing.
+    #[instrument(skip_all, fields(node = %node_id.fmt_short()))]
+    fn set_pinned_addr(&mut self, node_id: NodeId, addr: Option<SocketAddr>) {
+        let Some(addr) = addr else {
+            if let Some(node_state) = self.get_mut(NodeStateKey::NodeId(node_id)) {
+                node_state.set_pinned_addr(None, Source::App);
+            }
+            return;
+        };
+        let node_state = self.get_or_insert_with(NodeStateKey::NodeId(node_id), || Options {
+            node_id,
+            relay_url: None,
+            active: false,
+            source: Source::App,
+        });
+        node_state.set_pinned_addr(Some(addr), Source::App);
+        let id = node_state.id();
+        self.set_node_state_for_ip_port(addr, id);
+    }
+
     /// Prunes direct addresses from nodes that claim to share an address we know points to us.
     pub(super) fn on_direct_addr_discovered(&mut self, discovered: BTreeSet<SocketAddr>) {
         for addr in discovered {
@@ -330,7 +357,7 @@ impl NodeMapInner {
             if let Entry::Occupied(mut entry) = self.by_id.entry(id) {
                 let node = entry.get_mut();
                 node.remove_direct_addr(&ipp, reason);
-                if node.direct_addresses().count() == 0 {
+                if node.direct_addresses().count() == 0 && node.pinned_addr().is_none() {
                     let node_id = node.public_key();
                     let mapped_addr = node.quic_mapped_addr();
                     self.by_node_key.remove(node_id);
@@ -552,7 +579,7 @@ impl NodeMapInner {
         let mut prune_candidates: Vec<_> = self
             .by_id
             .values()
-            .filter(|node| !node.is_active(&now))
+            .filter(|node| !node.is_active(&now) && node.pinned_addr().is_none())
             .map(|node| (*node.public_key(), node.last_used()))
             .collect();
 
@@ -731,6 +758,47 @@ mod tests {
         (std::net::IpAddr::V4(Ipv4Addr::LOCALHOST), port).into()
     }
 
+    #[test]
+    fn test_pinned_addr() {
+        let _guard = iroh_test::logging::setup();
+
+        let node_map = NodeMap::default();
+        let node_id = SecretKey::generate().public();
+        let relay_url: RelayUrl = "https://my-relay-1.com".parse().unwrap();
+        node_map.add_test_addr(
+            NodeAddr::new(node_id)
+                .with_relay_url(relay_url.clone())
+                .with_direct_addresses([addr(4000)]),
+        );
+        let quic_addr = node_map.get_quic_mapped_addr_for_node_key(node_id).unwrap();
+
+        // Without a pin we do not yet trust the direct address and also use the relay.
+        let (_, _, relay, _) = node_map.get_send_addrs(quic_addr, false).unwrap();
+        assert_eq!(relay, Some(relay_url.clone()));
+
+        node_map.set_pinned_addr(node_id, Some(addr(4433)));
+        let (_, udp_addr, relay, _) = node_map.get_send_addrs(quic_addr, false).unwrap();
+        assert_eq!(udp_addr, Some(addr(4433)));
+        assert_eq!(relay, None);
+        let info = node_map.remote_info(node_id).unwrap();
+        assert_eq!(info.pinned_addr, Some(addr(4433)));
+        assert_eq!(info.conn_type, ConnectionType::Direct(addr(4433)));
+        assert_eq!(
+            node_map.receive_udp(addr(4433)).map(|(node_id, _)| node_id),
+            Some(node_id)
+        );
+
+        node_map.set_pinned_addr(node_id, None);
+        let (_, _, relay, _) = node_map.get_send_addrs(quic_addr, false).unwrap();
+        assert_eq!(relay, Some(relay_url));
+        assert_eq!(node_map.remote_info(node_id).unwrap().pinned_addr, None);
+
+        // Removing a pin does not add unknown nodes.
+        let unknown = SecretKey::generate().public();
+        node_map.set_pinned_addr(unknown, None);
+        assert!(node_map.remote_info(unknown).is_none());
+    }
+
     #[test]
     fn test_prune_direct_addresses() {
         let _guard = iroh_test::logging::setup();
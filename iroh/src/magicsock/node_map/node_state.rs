@@ -137,6 +137,11 @@ pub(super) struct NodeState {
     ///
     /// Used for metric reporting.
     has_been_direct: bool,
+    /// A direct address pinned by the application.
+    ///
+    /// When set, this address is always used to send to the node, bypassing the path
+    /// selection and never falling back to the relay.
+    pinned_addr: Option<SocketAddr>,
 }
 
 /// Options for creating a new [`NodeState`].
@@ -177,6 +182,7 @@ impl NodeState {
             last_call_me_maybe: None,
             conn_type: Watchable::new(ConnectionType::None),
             has_been_direct: false,
+            pinned_addr: None,
         }
     }
 
@@ -260,6 +266,7 @@ impl NodeState {
             conn_type,
             latency,
             last_used: self.last_used.map(|instant| now.duration_since(instant)),
+            pinned_addr: self.pinned_addr,
         }
     }
 
@@ -268,6 +275,30 @@ impl NodeState {
         self.relay_url.as_ref().map(|(url, _state)| url.clone())
     }
 
+    /// Returns the direct address pinned for this node, if any.
+    pub(super) fn pinned_addr(&self) -> Option<SocketAddr> {
+        self.pinned_addr
+    }
+
+    /// Pins a direct address to always send to, or removes the pin.
+    ///
+    /// The address is also added as a known path, so that disco keeps tracking its
+    /// latency.
+    pub(super) fn set_pinned_addr(&mut self, addr: Option<SocketAddr>, source: super::Source) {
+        if let Some(addr) = addr {
+            let now = Instant::now();
+            self.udp_paths
+                .paths
+                .entry(addr.into())
+                .and_modify(|path_state| {
+                    path_state.add_source(source.clone(), now);
+                })
+                .or_insert_with(|| PathState::new(self.node_id, SendAddr::from(addr), source, now));
+        }
+        debug!(pinned = ?addr, previous = ?self.pinned_addr, "updating pinned address");
+        self.pinned_addr = addr;
+    }
+
     /// Returns the address(es) that should be used for sending the next packet.
     ///
     /// This may return to send on one, both or no paths.
@@ -280,6 +311,10 @@ impl NodeState {
             debug!("in `DEV_relay_ONLY` mode, giving the relay address as the only viable address for this endpoint");
             return (None, self.relay_url());
         }
+        if let Some(addr) = self.pinned_addr {
+            trace!(%addr, "using pinned address");
+            return self.update_conn_type(Some(addr), None);
+        }
         let (best_addr, relay_url) = match self.udp_paths.send_addr(*now, have_ipv6) {
             UdpSendAddr::Valid(addr) => {
                 // If we have a valid address we use it.
@@ -302,6 +337,15 @@ impl NodeState {
                 (None, self.relay_url())
             }
         };
+        self.update_conn_type(best_addr, relay_url)
+    }
+
+    /// Updates the [`ConnectionType`] for the chosen send addresses and returns them.
+    fn update_conn_type(
+        &mut self,
+        best_addr: Option<SocketAddr>,
+        relay_url: Option<RelayUrl>,
+    ) -> (Option<SocketAddr>, Option<RelayUrl>) {
         let typ = match (best_addr, relay_url.clone()) {
             (Some(best_addr), Some(relay_url)) => ConnectionType::Mixed(best_addr, relay_url),
             (Some(best_addr), None) => ConnectionType::Direct(best_addr),
@@ -384,6 +428,10 @@ impl NodeState {
     #[instrument("want_call_me_maybe", skip_all)]
     fn want_call_me_maybe(&self, now: &Instant) -> bool {
         trace!("full ping: wanted?");
+        if self.pinned_addr.is_some() {
+            trace!("pinned address: not needed");
+            return false;
+        }
         let Some(last_full_ping) = self.last_full_ping else {
             debug!("no previous full ping: need full ping");
             return true;
@@ -1361,6 +1409,12 @@ pub struct RemoteInfo {
     /// from the remote node. Note that sending to the remote node does not imply
     /// the remote node received anything.
     pub last_used: Option<Duration>,
+    /// The direct address pinned for this node, if any.
+    ///
+    /// See [`Endpoint::set_pinned_addr`].
+    ///
+    /// [`Endpoint::set_pinned_addr`]: crate::endpoint::Endpoint::set_pinned_addr
+    pub pinned_addr: Option<SocketAddr>,
 }
 
 impl RemoteInfo {
@@ -1498,6 +1552,7 @@ mod tests {
                     last_call_me_maybe: None,
                     conn_type: Watchable::new(ConnectionType::Direct(ip_port.into())),
                     has_been_direct: true,
+                    pinned_addr: None,
                 },
                 ip_port.into(),
             )
@@ -1518,6 +1573,7 @@ mod tests {
                 last_call_me_maybe: None,
                 conn_type: Watchable::new(ConnectionType::Relay(send_addr.clone())),
                 has_been_direct: false,
+                pinned_addr: None,
             }
         };
 
@@ -1545,6 +1601,7 @@ mod tests {
                 last_call_me_maybe: None,
                 conn_type: Watchable::new(ConnectionType::Relay(send_addr.clone())),
                 has_been_direct: false,
+                pinned_addr: None,
             }
         };
 
@@ -1585,6 +1642,7 @@ mod tests {
                         send_addr.clone(),
                     )),
                     has_been_direct: false,
+                    pinned_addr: None,
                 },
                 socket_addr,
             )
@@ -1605,6 +1663,7 @@ mod tests {
                 conn_type: ConnectionType::Direct(a_socket_addr),
                 latency: Some(latency),
                 last_used: Some(elapsed),
+                pinned_addr: None,
             },
             RemoteInfo {
                 node_id: b_endpoint.node_id,
@@ -1617,6 +1676,7 @@ mod tests {
                 conn_type: ConnectionType::Relay(send_addr.clone()),
                 latency: Some(latency),
                 last_used: Some(elapsed),
+                pinned_addr: None,
             },
             RemoteInfo {
                 node_id: c_endpoint.node_id,
@@ -1629,6 +1689,7 @@ mod tests {
                 conn_type: ConnectionType::Relay(send_addr.clone()),
                 latency: None,
                 last_used: Some(elapsed),
+                pinned_addr: None,
             },
             RemoteInfo {
                 node_id: d_endpoint.node_id,
@@ -1648,6 +1709,7 @@ mod tests {
                 conn_type: ConnectionType::Mixed(d_socket_addr, send_addr.clone()),
                 latency: Some(Duration::from_millis(50)),
                 last_used: Some(elapsed),
+                pinned_addr: None,
             },
         ]);
 
@@ -40,6 +40,12 @@ pub(super) enum RelayActorMessage {
         contents: RelayContents,
         remote_node: NodeId,
     },
+    /// Sends a disco packet ahead of any queued [`RelayActorMessage::Send`] payloads.
+    SendDisco {
+        url: RelayUrl,
+        contents: RelayContents,
+        remote_node: NodeId,
+    },
     MaybeCloseRelaysOnRebind(Vec<IpAddr>),
     SetHome {
         url: RelayUrl,
@@ -54,6 +60,8 @@ struct ConnectedRelayActor {
     last_write: Instant,
     /// Queue to send received relay datagrams on.
     relay_datagrams_queue: Arc<RelayDatagramsQueue>,
+    /// The socket, to report connection failures to.
+    msock: Arc<MagicSock>,
     url: RelayUrl,
     relay_client: relay::client::Client,
     relay_client_receiver: relay::client::ClientReceiver,
@@ -85,10 +93,12 @@ impl ConnectedRelayActor {
         relay_client: relay::client::Client,
         relay_client_receiver: relay::client::ClientReceiver,
         relay_datagrams_queue: Arc<RelayDatagramsQueue>,
+        msock: Arc<MagicSock>,
     ) -> Self {
         ConnectedRelayActor {
             last_write: Instant::now(),
             relay_datagrams_queue,
+            msock,
             url,
             node_present: BTreeSet::new(),
             backoff: backoff::exponential::ExponentialBackoffBuilder::new()
@@ -104,10 +114,9 @@ impl ConnectedRelayActor {
 
     async fn run(mut self, mut inbox: mpsc::Receiver<ConnectedRelayMessage>) -> anyhow::Result<()> {
         debug!("initial dial {}", self.url);
-        self.relay_client
-            .connect()
-            .await
-            .context("initial connection")?;
+        let res = self.relay_client.connect().await;
+        self.msock.note_relay_connect(&self.url, res.is_ok());
+        res.context("initial connection")?;
 
         loop {
             // If a read error occurred on the connection it might have been lost.  But we
@@ -115,7 +124,9 @@ impl ConnectedRelayActor {
             // peers via the relay even if we don't start sending again first.
             if !self.relay_client.is_connected().await? {
                 debug!("relay re-connecting");
-                self.relay_client.connect().await.context("keepalive")?;
+                let res = self.relay_client.connect().await;
+                self.msock.note_relay_connect(&self.url, res.is_ok());
+                res.context("keepalive")?;
             }
             tokio::select! {
                 msg = inbox.recv() => {
@@ -308,7 +319,15 @@ impl RelayActor {
         self.cancel_token.clone()
     }
 
-    pub(super) async fn run(mut self, mut receiver: mpsc::Receiver<RelayActorMessage>) {
+    /// Runs the actor.
+    ///
+    /// Messages on `disco_receiver` are always handled before those on `receiver`, so that
+    /// hole punching can make progress while relayed payload is saturating the relay.
+    pub(super) async fn run(
+        mut self,
+        mut receiver: mpsc::Receiver<RelayActorMessage>,
+        mut disco_receiver: mpsc::Receiver<RelayActorMessage>,
+    ) {
         let mut cleanup_timer = time::interval_at(
             time::Instant::now() + RELAY_CLEAN_STALE_INTERVAL,
             RELAY_CLEAN_STALE_INTERVAL,
@@ -341,6 +360,11 @@ impl RelayActor {
                     }
                 }
 
+                Some(msg) = disco_receiver.recv() => {
+                    let cancel_token = self.cancel_token.child_token();
+                    cancel_token.run_until_cancelled(self.handle_msg(msg)).await;
+                }
+
                 msg = receiver.recv() => {
                     let Some(msg) = msg else {
                         trace!("shutting down relay recv loop");
@@ -368,7 +392,14 @@ impl RelayActor {
                 contents,
                 remote_node,
             } => {
-                self.send_relay(&url, contents, remote_node).await;
+                self.send_relay(&url, contents, remote_node, false).await;
+            }
+            RelayActorMessage::SendDisco {
+                url,
+                contents,
+                remote_node,
+            } => {
+                self.send_relay(&url, contents, remote_node, true).await;
             }
             RelayActorMessage::SetHome { url } => {
                 self.note_preferred(&url).await;
@@ -394,11 +425,22 @@ impl RelayActor {
         .await;
     }
 
-    async fn send_relay(&mut self, url: &RelayUrl, contents: RelayContents, remote_node: NodeId) {
+    /// Sends `contents` to `remote_node` via the relay at `url`.
+    ///
+    /// With `priority` set the packets skip ahead of any bulk payload already queued in the
+    /// relay client.
+    async fn send_relay(
+        &mut self,
+        url: &RelayUrl,
+        contents: RelayContents,
+        remote_node: NodeId,
+        priority: bool,
+    ) {
         trace!(
             %url,
             remote_node = %remote_node.fmt_short(),
             len = contents.iter().map(|c| c.len()).sum::<usize>(),
+            priority,
             "sending over relay",
         );
         // Relay Send
@@ -415,7 +457,12 @@ impl RelayActor {
         // and prefix them with a u16 packet size.  They then get sent as a single DISCO
         // frame.
         for packet in PacketizeIter::<_, PAYLAOD_SIZE>::new(contents) {
-            match relay_client.send(remote_node, packet).await {
+            let res = if priority {
+                relay_client.send_priority(remote_node, packet).await
+            } else {
+                relay_client.send(remote_node, packet).await
+            };
+            match res {
                 Ok(_) => {
                     inc_by!(MagicsockMetrics, send_relay, total_bytes);
                 }
@@ -520,7 +567,10 @@ impl RelayActor {
         if let Some(url) = self.msock.proxy_url() {
             builder = builder.proxy_url(url.clone());
         }
+        let (keepalive_interval, keepalive_timeout) = self.msock.relay_keepalive();
         let builder = builder
+            .keepalive_interval(keepalive_interval)
+            .keepalive_timeout(keepalive_timeout)
             .address_family_selector(move || {
                 let ipv6_reported = ipv6_reported.clone();
                 Box::pin(async move { ipv6_reported.load(Ordering::Relaxed) })
@@ -539,6 +589,7 @@ impl RelayActor {
             let url = url.clone();
             let relay_client = relay_client.clone();
             let relay_datagrams_queue = self.relay_datagrams_queue.clone();
+            let msock = self.msock.clone();
             let span = info_span!("conn-relay-actor", %url);
             async move {
                 let conn_actor = ConnectedRelayActor::new(
@@ -546,6 +597,7 @@ impl RelayActor {
                     relay_client,
                     relay_receiver,
                     relay_datagrams_queue,
+                    msock,
                 );
 
                 if let Err(err) = conn_actor.run(conn_actor_inbox_rx).await {
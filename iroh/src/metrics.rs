@@ -5,4 +5,6 @@ pub use iroh_relay::server::Metrics as RelayMetrics;
 pub use net_report::Metrics as NetReportMetrics;
 pub use portmapper::Metrics as PortmapMetrics;
 
-pub use crate::magicsock::Metrics as MagicsockMetrics;
+pub use crate::{
+    magicsock::Metrics as MagicsockMetrics, protocol::metrics::Metrics as RouterMetrics,
+};
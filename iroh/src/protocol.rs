@@ -38,16 +38,19 @@
 //!     }
 //! }
 //! ```
-use std::{collections::BTreeMap, sync::Arc};
+use std::{any::Any, collections::BTreeMap, panic::AssertUnwindSafe, sync::Arc};
 
 use anyhow::Result;
 use futures_buffered::join_all;
-use futures_lite::future::Boxed as BoxedFuture;
+use futures_lite::{future::Boxed as BoxedFuture, FutureExt};
+use iroh_metrics::inc;
 use tokio::{sync::Mutex, task::JoinSet};
 use tokio_util::{sync::CancellationToken, task::AbortOnDropHandle};
 use tracing::{error, info_span, trace, warn, Instrument};
 
-use crate::{endpoint::Connecting, Endpoint};
+use crate::{endpoint::Connecting, metrics::RouterMetrics, Endpoint};
+
+pub(crate) mod metrics;
 
 /// The built router.
 ///
@@ -109,6 +112,9 @@ pub trait ProtocolHandler: Send + Sync + std::fmt::Debug + 'static {
     /// Handle an incoming connection.
     ///
     /// This runs on a freshly spawned tokio task so this can be long-running.
+    ///
+    /// A panic in the returned future is caught and logged by the [`Router`], and only
+    /// affects this connection: it is dropped, which closes it.
     fn accept(&self, conn: Connecting) -> BoxedFuture<Result<()>>;
 
     /// Called when the node shuts down.
@@ -189,8 +195,9 @@ impl Router {
     ///
     /// If already shutdown, it returns `Ok`.
     ///
-    /// If some [`ProtocolHandler`] panicked in the accept loop, this will propagate
-    /// that panic into the result here.
+    /// If the accept loop itself panicked, this will propagate that panic into the result
+    /// here.  Panics in [`ProtocolHandler::accept`] are isolated to their connection and
+    /// do not end up here.
     pub async fn shutdown(&self) -> Result<()> {
         if self.is_shutdown() {
             return Ok(());
@@ -349,14 +356,46 @@ async fn handle_connection(incoming: crate::endpoint::Incoming, protocols: Arc<P
         warn!("Ignoring connection: unsupported ALPN protocol");
         return;
     };
-    if let Err(err) = handler.accept(connecting).await {
-        warn!("Handling incoming connection ended with error: {err}");
+    // The handler finishes the handshake, so the remote node id is not known here and
+    // only the remote address can be logged.
+    let remote_addr = connecting.remote_address();
+    // A misbehaving handler must not take the whole router down with it.  Catching the
+    // panic drops the connection, which closes it.
+    match AssertUnwindSafe(handler.accept(connecting))
+        .catch_unwind()
+        .await
+    {
+        Ok(Ok(())) => {}
+        Ok(Err(err)) => {
+            warn!("Handling incoming connection ended with error: {err}");
+        }
+        Err(panic) => {
+            inc!(RouterMetrics, protocol_handler_panics);
+            error!(
+                %remote_addr,
+                alpn = %String::from_utf8_lossy(&alpn),
+                "Protocol handler panicked: {}",
+                panic_message(&*panic),
+            );
+        }
+    }
+}
+
+/// Extracts the message from a panic payload, if it has one.
+fn panic_message(panic: &(dyn Any + Send)) -> &str {
+    if let Some(msg) = panic.downcast_ref::<&'static str>() {
+        msg
+    } else if let Some(msg) = panic.downcast_ref::<String>() {
+        msg
+    } else {
+        "<unknown>"
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::RelayMode;
 
     #[tokio::test]
     async fn test_shutdown() -> Result<()> {
@@ -373,4 +412,47 @@ mod tests {
 
         Ok(())
     }
+
+    #[derive(Debug)]
+    struct PanicHandler;
+
+    impl ProtocolHandler for PanicHandler {
+        fn accept(&self, connecting: Connecting) -> BoxedFuture<Result<()>> {
+            Box::pin(async move {
+                let _conn = connecting.await?;
+                panic!("handler bug");
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handler_panic_is_isolated() -> Result<()> {
+        let _guard = iroh_test::logging::setup();
+        const ALPN: &[u8] = b"/iroh/test/panic";
+
+        let endpoint = Endpoint::builder()
+            .relay_mode(RelayMode::Disabled)
+            .bind()
+            .await?;
+        let router = Router::builder(endpoint.clone())
+            .accept(ALPN, PanicHandler)
+            .spawn()
+            .await?;
+        let addr = endpoint.node_addr().await?;
+
+        let client = Endpoint::builder()
+            .relay_mode(RelayMode::Disabled)
+            .bind()
+            .await?;
+        for _ in 0..2 {
+            let conn = client.connect(addr.clone(), ALPN).await?;
+            // The connection is closed once the handler has panicked.
+            conn.closed().await;
+        }
+
+        assert!(!router.is_shutdown());
+        router.shutdown().await?;
+        client.close().await?;
+        Ok(())
+    }
 }
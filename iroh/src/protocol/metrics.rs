@@ -0,0 +1,26 @@
+use iroh_metrics::{
+    core::{Counter, Metric},
+    struct_iterable::Iterable,
+};
+
+/// Metrics for the protocol [`Router`](super::Router)
+#[derive(Debug, Clone, Iterable)]
+#[non_exhaustive]
+pub struct Metrics {
+    /// Number of incoming connections whose protocol handler panicked.
+    pub protocol_handler_panics: Counter,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self {
+            protocol_handler_panics: Counter::new("protocol_handler_panics"),
+        }
+    }
+}
+
+impl Metric for Metrics {
+    fn name() -> &'static str {
+        "router"
+    }
+}
@@ -105,6 +105,13 @@ pub async fn run_relay_server_with(
     Ok((m, url, server))
 }
 
+/// Parses the decrypted payload of a disco message, discarding the result.
+///
+/// The disco module is private, this is the entry point for the `disco_message` fuzz target.
+pub fn parse_disco_message(bytes: &[u8]) {
+    let _ = crate::disco::Message::from_bytes(bytes);
+}
+
 pub(crate) mod dns_and_pkarr_servers {
     use std::{net::SocketAddr, time::Duration};
 